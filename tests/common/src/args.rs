@@ -0,0 +1,179 @@
+//! Normalisation of the arguments Gevulot passes to a task.
+//!
+//! The gevulot-cli turns every `{"name": ..., "value": ...}` of `cmd_args`
+//! into the two arguments `[name, value]`, so a boolean flag reaches the
+//! program as `["--norm_stage", ""]` or `["--norm_stage", "true"]`, which
+//! clap rejects. Patched nodes drop the empty strings, unpatched ones don't.
+
+use clap::{ArgAction, CommandFactory, Parser};
+
+/// Parses the task arguments into `P` after normalising the boolean flags
+/// declared in `P` (the ones with `ArgAction::SetTrue` or `SetFalse`).
+pub fn parse_task_args<P: Parser>(args: &[String]) -> Result<P, clap::Error> {
+    let flags = bool_flags::<P>();
+    P::try_parse_from(normalize(args, &flags))
+}
+
+/// The `--long` and `-s` spellings of the boolean flags of `P`.
+fn bool_flags<P: CommandFactory>() -> Vec<String> {
+    P::command()
+        .get_arguments()
+        .filter(|a| matches!(a.get_action(), ArgAction::SetTrue | ArgAction::SetFalse))
+        .flat_map(|a| {
+            let long = a.get_long().map(|l| format!("--{l}"));
+            let short = a.get_short().map(|s| format!("-{s}"));
+            long.into_iter().chain(short)
+        })
+        .collect()
+}
+
+/// Rewrites `args` so that each boolean flag in `flags` appears alone when set
+/// and is dropped when unset.
+///
+/// A flag followed by `""`, `true`, `1` or `yes` is set, one followed by
+/// `false`, `0` or `no` is unset, and the value is consumed either way. A flag
+/// followed by anything else (or by nothing) is set and the next argument is
+/// left alone. `--flag=value` is read the same way, and kept as is for clap
+/// to reject when the value isn't a boolean.
+pub fn normalize(args: &[String], flags: &[String]) -> Vec<String> {
+    let mut out = Vec::with_capacity(args.len());
+    let mut iter = args.iter().peekable();
+    while let Some(arg) = iter.next() {
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) if arg.starts_with('-') => (name, Some(value)),
+            _ => (arg.as_str(), None),
+        };
+        if !flags.iter().any(|f| f == name) {
+            out.push(arg.clone());
+            continue;
+        }
+
+        let value = match inline {
+            Some(value) if parse_bool(value).is_none() => {
+                // let clap report the bad value
+                out.push(arg.clone());
+                continue;
+            }
+            Some(value) => Some(value),
+            None => match iter.peek().and_then(|v| parse_bool(v).map(|_| v.as_str())) {
+                Some(value) => {
+                    iter.next();
+                    Some(value)
+                }
+                None => None,
+            },
+        };
+        if value.and_then(parse_bool).unwrap_or(true) {
+            out.push(name.to_string());
+        }
+    }
+    out
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "" | "true" | "1" | "yes" => Some(true),
+        "false" | "0" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Parser)]
+    #[command(no_binary_name(true))]
+    struct Cli {
+        #[arg(long = "norm_stage")]
+        norm_stage: bool,
+        #[arg(long = "skip_main")]
+        skip_main: bool,
+        /// Set by default, cleared by the flag.
+        #[arg(long = "no_check", action = ArgAction::SetFalse)]
+        check: bool,
+        #[arg(long = "proof_file", default_value = "/workspace/proof.json")]
+        proof_file: String,
+        #[arg(long = "circom")]
+        circom: Option<String>,
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    fn parse(task_args: &[&str]) -> Cli {
+        parse_task_args::<Cli>(&args(task_args)).unwrap()
+    }
+
+    #[test]
+    fn bool_flags_lists_set_true_and_set_false_arguments() {
+        assert_eq!(
+            bool_flags::<Cli>(),
+            ["--norm_stage", "--skip_main", "--no_check"]
+        );
+    }
+
+    #[test]
+    fn empty_value_sets_the_flag() {
+        assert!(parse(&["--norm_stage", ""]).norm_stage);
+        assert_eq!(
+            normalize(&args(&["--norm_stage", ""]), &bool_flags::<Cli>()),
+            ["--norm_stage"]
+        );
+    }
+
+    #[test]
+    fn boolean_values_set_or_clear_the_flag() {
+        assert!(parse(&["--norm_stage", "true"]).norm_stage);
+        assert!(parse(&["--norm_stage", "1"]).norm_stage);
+        assert!(!parse(&["--norm_stage", "false"]).norm_stage);
+        assert!(!parse(&["--norm_stage", "0"]).norm_stage);
+        assert!(parse(&["--norm_stage=true"]).norm_stage);
+        assert!(!parse(&["--norm_stage=0"]).norm_stage);
+    }
+
+    #[test]
+    fn flag_followed_by_another_pair_is_set() {
+        let cli = parse(&["--norm_stage", "--proof_file", "/workspace/p.json"]);
+        assert!(cli.norm_stage);
+        assert_eq!(cli.proof_file, "/workspace/p.json");
+
+        let cli = parse(&[
+            "--skip_main",
+            "",
+            "--norm_stage",
+            "false",
+            "--proof_file",
+            "/workspace/p.json",
+        ]);
+        assert!(cli.skip_main);
+        assert!(!cli.norm_stage);
+        assert_eq!(cli.proof_file, "/workspace/p.json");
+    }
+
+    #[test]
+    fn set_false_flags() {
+        assert!(parse(&[]).check);
+        assert!(!parse(&["--no_check", ""]).check);
+        assert!(!parse(&["--no_check", "true"]).check);
+        assert!(parse(&["--no_check", "false"]).check);
+    }
+
+    #[test]
+    fn non_boolean_flag_keeps_its_empty_value() {
+        let flags = bool_flags::<Cli>();
+        assert_eq!(
+            normalize(&args(&["--circom", "", "--norm_stage", ""]), &flags),
+            ["--circom", "", "--norm_stage"]
+        );
+        let cli = parse(&["--circom", "", "--norm_stage", ""]);
+        assert_eq!(cli.circom.as_deref(), Some(""));
+        assert!(cli.norm_stage);
+    }
+
+    #[test]
+    fn bad_inline_value_is_left_for_clap() {
+        assert!(parse_task_args::<Cli>(&args(&["--norm_stage=maybe"])).is_err());
+    }
+}
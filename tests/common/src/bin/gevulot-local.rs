@@ -0,0 +1,58 @@
+use clap::Parser;
+use eigen_gevulot_common::harness::{parse_key_path, parse_tasks, Harness, Step};
+use std::path::PathBuf;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Runs a Gevulot workflow locally, without Gevulot or OPS.
+///
+/// Example, from the shell-test directory:
+/// gevulot-local --tasks tasks.json \
+///     --program PHSH=../../target/debug/prover --program VHSH=../../target/debug/verifier \
+///     --mount /gevulot=scripts/gevulot --input_dir input-files
+#[derive(Parser, Debug)]
+#[clap(version, about, long_about = None)]
+struct Cli {
+    /// The `--tasks` JSON array of `gevulot-cli exec`, or a file holding it.
+    #[clap(long, value_name = "TASK ARRAY")]
+    tasks: String,
+    /// The binary of a program, as `<program hash>=<binary path>`.
+    #[clap(long = "program", value_name = "HASH=BINARY", value_parser = parse_key_path)]
+    programs: Vec<(String, PathBuf)>,
+    /// A host directory standing in for an image path, as `<vm path>=<host dir>`.
+    #[clap(long = "mount", value_name = "VM_PATH=DIR", value_parser = parse_key_path)]
+    mounts: Vec<(String, PathBuf)>,
+    /// The directory holding the inputs given by `file_url`; nothing is downloaded.
+    #[clap(long = "input_dir")]
+    input_dir: Option<PathBuf>,
+    /// The directory for the step workspaces.
+    #[clap(long = "work_dir")]
+    work_dir: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    env_logger::init();
+    let cli = Cli::parse();
+
+    let json = match std::fs::read_to_string(&cli.tasks) {
+        Ok(json) => json,
+        Err(_) => cli.tasks.clone(),
+    };
+    let steps: Vec<Step> = parse_tasks(&json)?.iter().map(Step::from).collect();
+
+    let harness = Harness {
+        programs: cli.programs.into_iter().collect(),
+        mounts: cli.mounts,
+        input_dir: cli.input_dir,
+        work_dir: cli.work_dir,
+    };
+    for (i, result) in harness.run(&steps)?.iter().enumerate() {
+        println!("step {i}: program {}", result.program);
+        println!("  workspace: {}", result.workspace.display());
+        println!("  data: {}", String::from_utf8_lossy(&result.data));
+        for file in &result.files {
+            println!("  file: {file}");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use clap::Parser;
+use eigen_gevulot_common::harness::parse_key_path;
+use eigen_gevulot_common::mock_node::MockNode;
+use std::path::PathBuf;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// A local stand-in for a Gevulot node, running workflows with the local harness.
+///
+/// Example:
+/// gevulot-mock-node --program '#eigen-gevulot-prover=target/debug/prover' \
+///     --program '#eigen-gevulot-verifier=target/debug/verifier' \
+///     --mount /gevulot=tests/shell-test/scripts/gevulot --input_dir /data/http
+#[derive(Parser, Debug)]
+#[clap(version, about, long_about = None)]
+struct Cli {
+    #[clap(long = "listen_addr", default_value = "127.0.0.1:9944")]
+    listen_addr: String,
+    /// The base of the result file URLs; `http://<listen_addr>` by default.
+    #[clap(long = "public_url")]
+    public_url: Option<String>,
+    /// The binary of a program, as `<program hash or deployed name>=<binary path>`.
+    #[clap(long = "program", value_name = "PROGRAM=BINARY", value_parser = parse_key_path)]
+    programs: Vec<(String, PathBuf)>,
+    /// A host directory standing in for an image path, as `<vm path>=<host dir>`.
+    #[clap(long = "mount", value_name = "VM_PATH=DIR", value_parser = parse_key_path)]
+    mounts: Vec<(String, PathBuf)>,
+    /// The directory holding the inputs given by `file_url`; nothing is downloaded.
+    #[clap(long = "input_dir")]
+    input_dir: Option<PathBuf>,
+    /// The directory for the workflow runs.
+    #[clap(long = "work_dir")]
+    work_dir: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    env_logger::init();
+    let cli = Cli::parse();
+
+    let work_dir = cli.work_dir.unwrap_or_else(|| {
+        std::env::temp_dir().join(format!("gevulot-mock-{}", std::process::id()))
+    });
+    let public_url = cli
+        .public_url
+        .unwrap_or_else(|| format!("http://{}", cli.listen_addr));
+
+    let mut node = MockNode::new(work_dir, public_url);
+    node.programs = cli.programs.into_iter().collect();
+    node.mounts = cli.mounts;
+    node.input_dir = cli.input_dir;
+    node.serve(&cli.listen_addr)
+}
@@ -0,0 +1,387 @@
+//! The bootloader-input file a zkVM chunk is proved from.
+//!
+//! All integers are little-endian:
+//!
+//! | size       | field                                      |
+//! |------------|--------------------------------------------|
+//! | 8          | magic, `b"EGBLINP\0"`                      |
+//! | 4          | format version, currently 1                |
+//! | 4          | task name length `n`                       |
+//! | n          | task name, UTF-8                           |
+//! | 8          | chunk id                                   |
+//! | 8          | element count `c`                          |
+//! | 8          | `start_of_shutdown_routine`                |
+//! | 32         | blake3 of the payload                      |
+//! | 8 * c      | payload, canonical Goldilocks elements     |
+//!
+//! Files without the magic are read in the earlier raw layout: the
+//! `start_of_shutdown_routine` followed by the elements, with no task name
+//! or chunk id; they are reported as version 0.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+pub const MAGIC: &[u8; 8] = b"EGBLINP\0";
+pub const VERSION: u32 = 1;
+/// The version reported for files in the raw layout.
+pub const LEGACY_VERSION: u32 = 0;
+/// The Goldilocks modulus, `2^64 - 2^32 + 1`.
+pub const GOLDILOCKS_MODULUS: u64 = 0xffff_ffff_0000_0001;
+
+#[derive(Debug)]
+pub enum BootloaderInputError {
+    Io(std::io::Error),
+    /// The file ends before the `field` it should hold.
+    Truncated {
+        field: &'static str,
+        needed: usize,
+        left: usize,
+    },
+    /// Bytes follow the payload.
+    TrailingBytes(usize),
+    UnsupportedVersion(u32),
+    InvalidTaskName,
+    ChecksumMismatch {
+        expected: String,
+        actual: String,
+    },
+    /// A raw-layout file whose size is not a multiple of 8 bytes.
+    Misaligned(usize),
+    /// The element at `index` is not below the Goldilocks modulus.
+    NonCanonical {
+        index: usize,
+        value: u64,
+    },
+    /// The header names another task or chunk than the one being proved.
+    Mismatch {
+        expected: String,
+        found: String,
+    },
+}
+
+impl fmt::Display for BootloaderInputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Truncated {
+                field,
+                needed,
+                left,
+            } => write!(
+                f,
+                "truncated at the {field}: {needed} bytes needed, {left} left"
+            ),
+            Self::TrailingBytes(n) => write!(f, "{n} unexpected bytes after the payload"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported format version {v}"),
+            Self::InvalidTaskName => write!(f, "the task name is not UTF-8"),
+            Self::ChecksumMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "payload checksum mismatch: expected {expected}, got {actual}"
+                )
+            }
+            Self::Misaligned(len) => write!(f, "raw layout of {len} bytes is not a multiple of 8"),
+            Self::NonCanonical { index, value } => {
+                write!(
+                    f,
+                    "element {index} ({value:#x}) is not a Goldilocks element"
+                )
+            }
+            Self::Mismatch { expected, found } => write!(f, "expected {expected}, found {found}"),
+        }
+    }
+}
+
+impl std::error::Error for BootloaderInputError {}
+
+impl From<std::io::Error> for BootloaderInputError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+type Result<T> = std::result::Result<T, BootloaderInputError>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootloaderInput {
+    pub version: u32,
+    pub task_name: String,
+    pub chunk_id: u64,
+    pub start_of_shutdown_routine: u64,
+    pub elements: Vec<u64>,
+}
+
+impl BootloaderInput {
+    pub fn new(
+        task_name: &str,
+        chunk_id: u64,
+        start_of_shutdown_routine: u64,
+        elements: Vec<u64>,
+    ) -> Self {
+        BootloaderInput {
+            version: VERSION,
+            task_name: task_name.to_string(),
+            chunk_id,
+            start_of_shutdown_routine,
+            elements,
+        }
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        self.version == LEGACY_VERSION
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Writes the file in the current format.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        Ok(fs::write(path, self.to_bytes())?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if !bytes.starts_with(MAGIC) {
+            return Self::from_legacy_bytes(bytes);
+        }
+        let mut r = Reader(&bytes[MAGIC.len()..]);
+        let version = r.u32("version")?;
+        if version != VERSION {
+            return Err(BootloaderInputError::UnsupportedVersion(version));
+        }
+        let name_len = r.u32("task name length")? as usize;
+        let task_name = String::from_utf8(r.take("task name", name_len)?.to_vec())
+            .map_err(|_| BootloaderInputError::InvalidTaskName)?;
+        let chunk_id = r.u64("chunk id")?;
+        let count = r.u64("element count")? as usize;
+        let start_of_shutdown_routine = r.u64("shutdown routine")?;
+        let checksum = r.take("payload checksum", 32)?;
+        let payload = r.take("payload", count.saturating_mul(8))?;
+        if !r.0.is_empty() {
+            return Err(BootloaderInputError::TrailingBytes(r.0.len()));
+        }
+        let actual = blake3::hash(payload);
+        if actual.as_bytes() != checksum {
+            return Err(BootloaderInputError::ChecksumMismatch {
+                expected: hex(checksum),
+                actual: actual.to_string(),
+            });
+        }
+        Ok(BootloaderInput {
+            version,
+            task_name,
+            chunk_id,
+            start_of_shutdown_routine,
+            elements: elements(payload)?,
+        })
+    }
+
+    fn from_legacy_bytes(bytes: &[u8]) -> Result<Self> {
+        if !bytes.chunks_exact(8).remainder().is_empty() {
+            return Err(BootloaderInputError::Misaligned(bytes.len()));
+        }
+        let mut r = Reader(bytes);
+        let start_of_shutdown_routine = r.u64("shutdown routine")?;
+        Ok(BootloaderInput {
+            version: LEGACY_VERSION,
+            task_name: String::new(),
+            chunk_id: 0,
+            start_of_shutdown_routine,
+            elements: elements(r.0)?,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let payload: Vec<u8> = self.elements.iter().flat_map(|e| e.to_le_bytes()).collect();
+        let mut out = Vec::with_capacity(80 + self.task_name.len() + payload.len());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&(self.task_name.len() as u32).to_le_bytes());
+        out.extend_from_slice(self.task_name.as_bytes());
+        out.extend_from_slice(&self.chunk_id.to_le_bytes());
+        out.extend_from_slice(&(self.elements.len() as u64).to_le_bytes());
+        out.extend_from_slice(&self.start_of_shutdown_routine.to_le_bytes());
+        out.extend_from_slice(blake3::hash(&payload).as_bytes());
+        out.extend_from_slice(&payload);
+        out
+    }
+
+    /// Fails unless the header names `task_name` and `chunk_id`. Files in the
+    /// raw layout carry neither and always pass.
+    pub fn check_task(&self, task_name: &str, chunk_id: u64) -> Result<()> {
+        if self.is_legacy() || (self.task_name == task_name && self.chunk_id == chunk_id) {
+            return Ok(());
+        }
+        Err(BootloaderInputError::Mismatch {
+            expected: format!("chunk {chunk_id} of {task_name}"),
+            found: format!("chunk {} of {}", self.chunk_id, self.task_name),
+        })
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, field: &'static str, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(BootloaderInputError::Truncated {
+                field,
+                needed: n,
+                left: self.0.len(),
+            });
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn u32(&mut self, field: &'static str) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(field, 4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self, field: &'static str) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(field, 8)?.try_into().unwrap()))
+    }
+}
+
+fn elements(payload: &[u8]) -> Result<Vec<u64>> {
+    payload
+        .chunks_exact(8)
+        .enumerate()
+        .map(|(index, bytes)| {
+            let value = u64::from_le_bytes(bytes.try_into().unwrap());
+            if value >= GOLDILOCKS_MODULUS {
+                return Err(BootloaderInputError::NonCanonical { index, value });
+            }
+            Ok(value)
+        })
+        .collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input() -> BootloaderInput {
+        BootloaderInput::new("lr", 3, 42, vec![0, 1, GOLDILOCKS_MODULUS - 1])
+    }
+
+    /// The offset of the element count in the header of [`input`].
+    const COUNT_OFFSET: usize = 8 + 4 + 4 + 2 + 8;
+
+    #[test]
+    fn written_files_read_back() {
+        let path = std::env::temp_dir().join(format!("bootloader-{}.bin", std::process::id()));
+        input().write(&path).unwrap();
+        let read = BootloaderInput::read(&path).unwrap();
+        assert_eq!(read, input());
+        assert!(!read.is_legacy());
+        read.check_task("lr", 3).unwrap();
+        assert!(matches!(
+            read.check_task("lr", 4),
+            Err(BootloaderInputError::Mismatch { .. })
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn headerless_files_are_read_in_the_raw_layout() {
+        let bytes: Vec<u8> = [42u64, 7, 8].iter().flat_map(|e| e.to_le_bytes()).collect();
+        let read = BootloaderInput::from_bytes(&bytes).unwrap();
+        assert!(read.is_legacy());
+        assert_eq!(read.start_of_shutdown_routine, 42);
+        assert_eq!(read.elements, [7, 8]);
+        // Any task and chunk may be proved from them.
+        read.check_task("lr", 3).unwrap();
+
+        assert!(matches!(
+            BootloaderInput::from_bytes(&bytes[..20]),
+            Err(BootloaderInputError::Misaligned(20))
+        ));
+    }
+
+    #[test]
+    fn truncated_files_name_the_missing_field() {
+        let bytes = input().to_bytes();
+        assert!(matches!(
+            BootloaderInput::from_bytes(&bytes[..MAGIC.len() + 2]),
+            Err(BootloaderInputError::Truncated {
+                field: "version",
+                ..
+            })
+        ));
+        assert!(matches!(
+            BootloaderInput::from_bytes(&bytes[..COUNT_OFFSET + 4]),
+            Err(BootloaderInputError::Truncated {
+                field: "element count",
+                ..
+            })
+        ));
+        assert!(matches!(
+            BootloaderInput::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(BootloaderInputError::Truncated {
+                field: "payload",
+                needed: 24,
+                left: 23
+            })
+        ));
+    }
+
+    #[test]
+    fn element_counts_and_checksums_must_match_the_payload() {
+        let mut bytes = input().to_bytes();
+        bytes[COUNT_OFFSET] = 4;
+        assert!(matches!(
+            BootloaderInput::from_bytes(&bytes),
+            Err(BootloaderInputError::Truncated {
+                field: "payload",
+                ..
+            })
+        ));
+        bytes[COUNT_OFFSET] = 2;
+        assert!(matches!(
+            BootloaderInput::from_bytes(&bytes),
+            Err(BootloaderInputError::TrailingBytes(8))
+        ));
+
+        let mut bytes = input().to_bytes();
+        let last = bytes.len() - 1;
+        bytes[last - 8] ^= 1;
+        assert!(matches!(
+            BootloaderInput::from_bytes(&bytes),
+            Err(BootloaderInputError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn trailing_bytes_are_rejected() {
+        let mut bytes = input().to_bytes();
+        bytes.extend_from_slice(&[0; 3]);
+        assert!(matches!(
+            BootloaderInput::from_bytes(&bytes),
+            Err(BootloaderInputError::TrailingBytes(3))
+        ));
+    }
+
+    #[test]
+    fn elements_must_be_canonical() {
+        let bytes = BootloaderInput::new("lr", 0, 0, vec![5, GOLDILOCKS_MODULUS]).to_bytes();
+        assert!(matches!(
+            BootloaderInput::from_bytes(&bytes),
+            Err(BootloaderInputError::NonCanonical {
+                index: 1,
+                value: GOLDILOCKS_MODULUS
+            })
+        ));
+        let raw: Vec<u8> = [0, u64::MAX].iter().flat_map(|e| e.to_le_bytes()).collect();
+        assert!(matches!(
+            BootloaderInput::from_bytes(&raw),
+            Err(BootloaderInputError::NonCanonical { index: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,194 @@
+//! The bootloader-input files of a zkVM program and their manifest.
+//!
+//! The chunks of a run are written next to each other as
+//! `<task>_chunks_<i>.data`, with a `manifest.json` holding what a task
+//! needs to reference them: the file of each chunk with its blake3, and the
+//! blake3 of the compiled `.asm` the prover recompiles against and of the
+//! trace file the guest reads, both copied next to the chunks.
+
+use crate::bootloader::BootloaderInput;
+use crate::harness::file_checksum;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkEntry {
+    pub chunk_id: u64,
+    /// The file name, relative to the manifest.
+    pub file: String,
+    pub blake3: String,
+    pub elements: u64,
+    pub start_of_shutdown_routine: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkManifest {
+    pub task_name: String,
+    /// The compiled program, relative to the manifest.
+    pub asm_file: String,
+    pub asm_blake3: String,
+    /// The input data of the guest, relative to the manifest.
+    pub trace_file: String,
+    pub trace_blake3: String,
+    pub chunk_count: u64,
+    pub chunks: Vec<ChunkEntry>,
+}
+
+impl ChunkManifest {
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)
+            .map_err(|e| format!("parsing {}: {e}", path.display()))?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+pub fn chunk_file_name(task_name: &str, chunk_id: u64) -> String {
+    format!("{task_name}_chunks_{chunk_id}.data")
+}
+
+/// Writes one bootloader-input file per `(elements, start_of_shutdown_routine)`
+/// chunk into `out_dir`, then the manifest, which is returned with its path.
+/// The `.asm` and the trace file are copied into `out_dir`.
+pub fn write_chunks(
+    task_name: &str,
+    asm_file: &Path,
+    trace_file: &Path,
+    chunks: Vec<(Vec<u64>, u64)>,
+    out_dir: &Path,
+) -> Result<(ChunkManifest, PathBuf)> {
+    fs::create_dir_all(out_dir)?;
+    let asm_name = copy_into(asm_file, out_dir)?;
+    let trace_name = copy_into(trace_file, out_dir)?;
+
+    let mut entries = Vec::with_capacity(chunks.len());
+    for (chunk_id, (elements, start_of_shutdown_routine)) in (0u64..).zip(chunks) {
+        let file = chunk_file_name(task_name, chunk_id);
+        let path = out_dir.join(&file);
+        let count = elements.len() as u64;
+        BootloaderInput::new(task_name, chunk_id, start_of_shutdown_routine, elements)
+            .write(&path)?;
+        entries.push(ChunkEntry {
+            chunk_id,
+            blake3: file_checksum(&path)?,
+            file,
+            elements: count,
+            start_of_shutdown_routine,
+        });
+    }
+
+    let manifest = ChunkManifest {
+        task_name: task_name.to_string(),
+        asm_blake3: file_checksum(&out_dir.join(&asm_name))?,
+        asm_file: asm_name,
+        trace_blake3: file_checksum(&out_dir.join(&trace_name))?,
+        trace_file: trace_name,
+        chunk_count: entries.len() as u64,
+        chunks: entries,
+    };
+    let path = out_dir.join(MANIFEST_FILE);
+    manifest.save(&path)?;
+    Ok((manifest, path))
+}
+
+/// Copies `file` into `dir`, unless it is there already, and returns its name.
+fn copy_into(file: &Path, dir: &Path) -> Result<String> {
+    let name = file
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("{} has no file name", file.display()))?;
+    if fs::canonicalize(file)? != fs::canonicalize(dir)?.join(name) {
+        fs::copy(file, dir.join(name))?;
+    }
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bootloader::BootloaderInputError;
+
+    /// Writes two chunks of `lr` under a fresh directory, from a `.asm` and a
+    /// trace file outside of it.
+    fn write_test_chunks(name: &str) -> (PathBuf, ChunkManifest) {
+        let root = std::env::temp_dir().join(format!("chunks-{name}-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("lr.asm"), "machine Main {}").unwrap();
+        fs::write(root.join("trace.json"), "{}").unwrap();
+        let out_dir = root.join("output");
+        let chunks = vec![(vec![1, 2, 3], 10), (vec![4, 5], 20)];
+        let (manifest, path) = write_chunks(
+            "lr",
+            &root.join("lr.asm"),
+            &root.join("trace.json"),
+            chunks,
+            &out_dir,
+        )
+        .unwrap();
+        assert_eq!(path, out_dir.join(MANIFEST_FILE));
+        (root, manifest)
+    }
+
+    #[test]
+    fn manifest_lists_every_chunk_with_its_checksum() {
+        let (root, manifest) = write_test_chunks("manifest");
+        let out_dir = root.join("output");
+        let loaded = ChunkManifest::load(&out_dir.join(MANIFEST_FILE)).unwrap();
+        assert_eq!(loaded, manifest);
+
+        assert_eq!(loaded.chunk_count, 2);
+        assert_eq!(loaded.chunks.len(), 2);
+        for (chunk, (elements, shutdown)) in loaded.chunks.iter().zip([(3, 10), (2, 20)]) {
+            assert_eq!(chunk.file, chunk_file_name("lr", chunk.chunk_id));
+            assert_eq!(
+                chunk.blake3,
+                file_checksum(&out_dir.join(&chunk.file)).unwrap()
+            );
+            assert_eq!(
+                (chunk.elements, chunk.start_of_shutdown_routine),
+                (elements, shutdown)
+            );
+            let input = BootloaderInput::read(&out_dir.join(&chunk.file)).unwrap();
+            input.check_task("lr", chunk.chunk_id).unwrap();
+        }
+
+        assert_eq!(loaded.asm_file, "lr.asm");
+        assert_eq!(
+            loaded.asm_blake3,
+            blake3::hash(b"machine Main {}").to_string()
+        );
+        assert_eq!(loaded.trace_file, "trace.json");
+        assert_eq!(loaded.trace_blake3, blake3::hash(b"{}").to_string());
+        assert!(out_dir.join("lr.asm").is_file());
+        assert!(out_dir.join("trace.json").is_file());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn tampered_chunks_are_detected() {
+        let (root, manifest) = write_test_chunks("tampered");
+        let chunk = &manifest.chunks[1];
+        let path = root.join("output").join(&chunk.file);
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        fs::write(&path, bytes).unwrap();
+
+        // Against the manifest, as the node checks a downloaded input,
+        assert_ne!(file_checksum(&path).unwrap(), chunk.blake3);
+        // and against the payload checksum, as the prover reads it.
+        assert!(matches!(
+            BootloaderInput::read(&path),
+            Err(BootloaderInputError::ChecksumMismatch { .. })
+        ));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
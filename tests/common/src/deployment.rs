@@ -0,0 +1,30 @@
+//! The record of a prover/verifier deployment, as written by the e2e
+//! client's `deploy` command.
+
+use crate::tx::ProgramMetadata;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deployment {
+    pub name: String,
+    /// The hash of the Deploy transaction.
+    pub tx_hash: String,
+    pub prover: ProgramMetadata,
+    pub verifier: ProgramMetadata,
+}
+
+impl Deployment {
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)
+            .map_err(|e| format!("parsing {}: {e}", path.display()))?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
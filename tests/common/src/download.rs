@@ -0,0 +1,167 @@
+//! Downloads the files a Proof or Verification transaction lists.
+//!
+//! Each file is stored under the output directory at its `vm_path`, and is
+//! only moved into place once its blake3 checksum matches the one the node
+//! recorded.
+
+use crate::harness::file_checksum;
+use crate::tx::TxFile;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The path under `out_dir` for a file returned at `vm_path`.
+pub fn local_path(out_dir: &Path, vm_path: &str) -> Result<PathBuf> {
+    let relative = Path::new(vm_path.trim_start_matches('/'));
+    if relative.as_os_str().is_empty()
+        || !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return Err(format!(
+            "refusing to store {vm_path} outside of {}",
+            out_dir.display()
+        )
+        .into());
+    }
+    Ok(out_dir.join(relative))
+}
+
+/// Downloads `file` to its path under `out_dir` and checks its checksum.
+pub fn download_file(file: &TxFile, out_dir: &Path) -> Result<PathBuf> {
+    let target = local_path(out_dir, &file.vm_path)?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut partial = target.clone().into_os_string();
+    partial.push(".part");
+    let partial = PathBuf::from(partial);
+
+    log::info!("downloading {}", file.url);
+    // A failed or corrupt download leaves no `.part` file behind.
+    if let Err(e) = fetch_checked(file, &partial) {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::rename(&partial, &target)?;
+    Ok(target)
+}
+
+/// Writes `file` to `partial` and checks its checksum.
+fn fetch_checked(file: &TxFile, partial: &Path) -> Result<()> {
+    let response = ureq::get(&file.url)
+        .call()
+        .map_err(|e| format!("downloading {}: {e}", file.url))?;
+    let mut out = fs::File::create(partial)?;
+    std::io::copy(&mut response.into_reader(), &mut out)?;
+    drop(out);
+
+    let checksum = file_checksum(partial)?;
+    if checksum != file.checksum {
+        return Err(format!(
+            "checksum mismatch for {}: expected {}, got {checksum}",
+            file.vm_path, file.checksum
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Downloads all `files` under `out_dir`. Every file is attempted; the error
+/// lists each one that failed.
+pub fn download_files(files: &[TxFile], out_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let mut errors = Vec::new();
+    for file in files {
+        match download_file(file, out_dir) {
+            Ok(path) => paths.push(path),
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(format!(
+            "{} of {} files failed: {}",
+            errors.len(),
+            files.len(),
+            errors.join("; ")
+        )
+        .into());
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    /// Serves `body` to the first request and returns its URL.
+    fn serve_once(body: &'static [u8]) -> String {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/proof.json", server.server_addr());
+        thread::spawn(move || {
+            if let Ok(request) = server.recv() {
+                let _ = request.respond(tiny_http::Response::from_data(body));
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn local_path_stays_under_the_output_directory() {
+        let out = Path::new("/tmp/results");
+        assert_eq!(
+            local_path(out, "/workspace/proof.json").unwrap(),
+            out.join("workspace/proof.json")
+        );
+        assert_eq!(
+            local_path(out, "proof.json").unwrap(),
+            out.join("proof.json")
+        );
+        // Empty and `.` components collapse, and stay under the directory.
+        assert_eq!(
+            local_path(out, "//workspace//./proof.json").unwrap(),
+            out.join("workspace/proof.json")
+        );
+        for vm_path in [
+            "",
+            "/",
+            "/workspace/../../etc/passwd",
+            "../proof.json",
+            "//etc/passwd/..",
+        ] {
+            assert!(local_path(out, vm_path).is_err(), "{vm_path}");
+        }
+    }
+
+    #[test]
+    fn downloads_are_moved_into_place_once_checked() {
+        let dir = std::env::temp_dir().join(format!("download-ok-{}", std::process::id()));
+        let file = TxFile {
+            url: serve_once(b"{}"),
+            checksum: blake3::hash(b"{}").to_string(),
+            vm_path: "/workspace/proof.json".to_string(),
+        };
+        let path = download_file(&file, &dir).unwrap();
+        assert_eq!(path, dir.join("workspace/proof.json"));
+        assert_eq!(fs::read(&path).unwrap(), b"{}");
+        assert!(!dir.join("workspace/proof.json.part").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn checksum_mismatches_leave_no_file_behind() {
+        let dir = std::env::temp_dir().join(format!("download-bad-{}", std::process::id()));
+        let file = TxFile {
+            url: serve_once(b"tampered"),
+            checksum: blake3::hash(b"{}").to_string(),
+            vm_path: "/workspace/proof.json".to_string(),
+        };
+        let err = download_file(&file, &dir).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"), "{err}");
+        assert!(!dir.join("workspace/proof.json").exists());
+        assert!(!dir.join("workspace/proof.json.part").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
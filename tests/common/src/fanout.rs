@@ -0,0 +1,185 @@
+//! One workflow per zkVM chunk, and the state file tracking them.
+//!
+//! The workflow of each chunk is the job file with `{task}`, `{chunk_id}` and
+//! `{trace_file}` replaced in every argument, path and forwarded file, and
+//! with the chunk's bootloader-input file and the compiled `.asm` and trace
+//! file of the manifest added to the prover inputs, as published under the
+//! file server. Local files of the
+//! job are published there too unless the job names its own server.
+
+use crate::chunks::{ChunkEntry, ChunkManifest};
+use crate::harness::CmdArg;
+use crate::progress::WorkflowStatus;
+use crate::workflow::{Job, JobInput, JobStep};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The job of chunk `chunk` of the manifest.
+pub fn chunk_job(
+    job: &Job,
+    manifest: &ChunkManifest,
+    chunk: &ChunkEntry,
+    file_server: &str,
+) -> Job {
+    let fill = |s: &str| {
+        s.replace("{task}", &manifest.task_name)
+            .replace("{chunk_id}", &chunk.chunk_id.to_string())
+            .replace("{trace_file}", &manifest.trace_file)
+    };
+    let fill_step = |step: &JobStep| JobStep {
+        program: step.program.clone(),
+        args: step
+            .args
+            .iter()
+            .map(|a| CmdArg {
+                name: a.name.clone(),
+                value: fill(&a.value),
+            })
+            .collect(),
+        inputs: step
+            .inputs
+            .iter()
+            .map(|input| match input {
+                JobInput::File { file, vm_path } => JobInput::File {
+                    file: file.clone(),
+                    vm_path: vm_path.as_deref().map(fill),
+                },
+                JobInput::Url {
+                    url,
+                    checksum,
+                    vm_path,
+                } => JobInput::Url {
+                    url: fill(url),
+                    checksum: checksum.clone(),
+                    vm_path: fill(vm_path),
+                },
+            })
+            .collect(),
+        forward: step.forward.iter().map(|f| fill(f)).collect(),
+    };
+
+    let server = file_server.trim_end_matches('/');
+    let mut prover = fill_step(&job.prover);
+    prover.inputs.push(JobInput::Url {
+        url: format!("{server}/{}", chunk.file),
+        checksum: chunk.blake3.clone(),
+        vm_path: format!("/workspace/{}", chunk.file),
+    });
+    prover.inputs.push(JobInput::Url {
+        url: format!("{server}/{}", manifest.asm_file),
+        checksum: manifest.asm_blake3.clone(),
+        vm_path: format!("/workspace/{}", manifest.asm_file),
+    });
+    prover.inputs.push(JobInput::Url {
+        url: format!("{server}/{}", manifest.trace_file),
+        checksum: manifest.trace_blake3.clone(),
+        vm_path: format!("/workspace/{}", manifest.trace_file),
+    });
+    Job {
+        file_server: job.file_server.clone().or_else(|| Some(server.to_string())),
+        prover,
+        verifier: fill_step(&job.verifier),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkStatus {
+    /// Not submitted yet.
+    Pending,
+    /// The Run transaction could not be built or sent.
+    Failed(String),
+    Submitted,
+    Proved,
+    Verified,
+    Rejected(String),
+    /// The deadline passed; the transaction is watched again on the next run.
+    TimedOut,
+}
+
+impl ChunkStatus {
+    /// Whether a workflow is in flight that the next run should wait for
+    /// rather than submit again.
+    pub fn is_in_flight(&self) -> bool {
+        matches!(
+            self,
+            ChunkStatus::Submitted | ChunkStatus::Proved | ChunkStatus::TimedOut
+        )
+    }
+}
+
+impl From<&WorkflowStatus> for ChunkStatus {
+    fn from(status: &WorkflowStatus) -> Self {
+        match status {
+            WorkflowStatus::Pending => ChunkStatus::Submitted,
+            WorkflowStatus::Proved => ChunkStatus::Proved,
+            WorkflowStatus::Verified => ChunkStatus::Verified,
+            WorkflowStatus::Rejected(reason) => ChunkStatus::Rejected(reason.clone()),
+            WorkflowStatus::TimedOut => ChunkStatus::TimedOut,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkState {
+    pub tx_hash: Option<String>,
+    pub status: ChunkStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FanoutState {
+    pub task_name: String,
+    pub chunks: BTreeMap<u64, ChunkState>,
+}
+
+impl FanoutState {
+    /// The state in `path`, or a fresh one for `manifest` when there is
+    /// none. Chunks missing from the file are added as pending.
+    pub fn load_or_new(path: &Path, manifest: &ChunkManifest) -> Result<Self> {
+        let mut state = if path.exists() {
+            let state: FanoutState = serde_json::from_slice(&fs::read(path)?)
+                .map_err(|e| format!("parsing {}: {e}", path.display()))?;
+            if state.task_name != manifest.task_name {
+                return Err(format!(
+                    "{} tracks {}, not {}",
+                    path.display(),
+                    state.task_name,
+                    manifest.task_name
+                )
+                .into());
+            }
+            state
+        } else {
+            FanoutState {
+                task_name: manifest.task_name.clone(),
+                chunks: BTreeMap::new(),
+            }
+        };
+        for chunk in &manifest.chunks {
+            state.chunks.entry(chunk.chunk_id).or_insert(ChunkState {
+                tx_hash: None,
+                status: ChunkStatus::Pending,
+            });
+        }
+        Ok(state)
+    }
+
+    /// Writes the state through a temporary file, so an interrupted run
+    /// never leaves it half written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    pub fn all_verified(&self) -> bool {
+        self.chunks
+            .values()
+            .all(|c| c.status == ChunkStatus::Verified)
+    }
+}
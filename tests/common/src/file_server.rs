@@ -0,0 +1,375 @@
+//! Serves the local input files of a workflow from the client, so the node
+//! can download them without a separately hosted file server.
+//!
+//! Each file is served content-addressed as `/<blake3>/<file name>`, with
+//! the name percent-encoded in its URL. The server answers single `Range`
+//! requests, ignores the query of a request and writes every request to the
+//! access log.
+//! A file counts as fetched once its last byte has been sent; the server
+//! stops by itself when every file has been fetched.
+
+use crate::harness::file_checksum;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tiny_http::{Header, Method, Request, Response, Server, StatusCode};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Debug, Clone)]
+struct ServedFile {
+    path: PathBuf,
+    len: u64,
+    fetched: bool,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// The files by URL path, percent-decoded.
+    files: BTreeMap<String, ServedFile>,
+}
+
+impl State {
+    fn pending(&self) -> usize {
+        self.files.values().filter(|f| !f.fetched).count()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FileServer {
+    /// The base of the file URLs, eg. `http://192.168.1.10:8080`.
+    public_url: String,
+    state: State,
+}
+
+impl FileServer {
+    pub fn new(public_url: impl Into<String>) -> Self {
+        FileServer {
+            public_url: public_url.into().trim_end_matches('/').to_string(),
+            state: State::default(),
+        }
+    }
+
+    /// Adds `path` to the served files and returns its URL and blake3.
+    pub fn add(&mut self, path: &Path) -> Result<(String, String)> {
+        let checksum = file_checksum(path)?;
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| format!("{} has no file name", path.display()))?;
+        let url_path = format!("/{checksum}/{name}");
+        let url = format!("{}/{checksum}/{}", self.public_url, percent_encode(name));
+        self.state.files.insert(
+            url_path,
+            ServedFile {
+                path: path.to_path_buf(),
+                len: fs::metadata(path)?.len(),
+                fetched: false,
+            },
+        );
+        Ok((url, checksum))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.files.is_empty()
+    }
+
+    /// Binds `addr` and serves the files from a background thread.
+    pub fn start(self, addr: &str) -> Result<RunningFileServer> {
+        let server = Arc::new(Server::http(addr).map_err(|e| format!("binding {addr}: {e}"))?);
+        log::info!(
+            "serving {} input files on {addr} as {}",
+            self.state.files.len(),
+            self.public_url
+        );
+        let shared = Arc::new((Mutex::new(self.state), Condvar::new()));
+        let thread = {
+            let (server, shared) = (server.clone(), shared.clone());
+            std::thread::spawn(move || serve(&server, &shared))
+        };
+        Ok(RunningFileServer {
+            server,
+            shared,
+            thread: Some(thread),
+        })
+    }
+}
+
+pub struct RunningFileServer {
+    server: Arc<Server>,
+    shared: Arc<(Mutex<State>, Condvar)>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RunningFileServer {
+    /// The number of files not fetched yet.
+    pub fn pending(&self) -> usize {
+        self.shared.0.lock().unwrap().pending()
+    }
+
+    /// Waits until every file has been fetched, or `timeout` has passed.
+    /// Returns whether every file was fetched.
+    pub fn wait_fetched(&self, timeout: Duration) -> bool {
+        let (state, fetched) = &*self.shared;
+        let state = fetched
+            .wait_timeout_while(state.lock().unwrap(), timeout, |s| s.pending() > 0)
+            .unwrap()
+            .0;
+        state.pending() == 0
+    }
+
+    /// Stops serving, whether or not every file has been fetched.
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for RunningFileServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn serve(server: &Server, shared: &(Mutex<State>, Condvar)) {
+    let (state, fetched) = shared;
+    if state.lock().unwrap().pending() == 0 {
+        return;
+    }
+    for request in server.incoming_requests() {
+        let remote = request
+            .remote_addr()
+            .map(|a| a.to_string())
+            .unwrap_or_default();
+        let line = format!("{remote} {} {}", request.method(), request.url());
+        let url = url_path(request.url());
+        let file = state.lock().unwrap().files.get(&url).cloned();
+        match respond(request, file.as_ref()) {
+            Ok((status, sent, complete)) => {
+                log::info!("{line} {status} {sent}");
+                if complete {
+                    let mut state = state.lock().unwrap();
+                    if let Some(file) = state.files.get_mut(&url) {
+                        file.fetched = true;
+                    }
+                    if state.pending() == 0 {
+                        log::info!("every input file has been fetched, stopping the file server");
+                        fetched.notify_all();
+                        return;
+                    }
+                }
+            }
+            Err(e) => log::warn!("{line} failed: {e}"),
+        }
+    }
+}
+
+/// Answers one request and returns the status, the bytes sent and whether
+/// the last byte of the file was sent.
+fn respond(request: Request, file: Option<&ServedFile>) -> Result<(u16, u64, bool)> {
+    let head = match request.method() {
+        Method::Get => false,
+        Method::Head => true,
+        _ => {
+            request.respond(Response::empty(405))?;
+            return Ok((405, 0, false));
+        }
+    };
+    let Some(file) = file else {
+        request.respond(Response::empty(404))?;
+        return Ok((404, 0, false));
+    };
+    let range = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Range"))
+        .map(|h| parse_range(h.value.as_str(), file.len));
+
+    let (status, start, end) = match range {
+        None | Some(Range::Ignored) => (200, 0, file.len),
+        Some(Range::Bytes(start, end)) => (206, start, end),
+        Some(Range::Unsatisfiable) => {
+            let content_range = header("Content-Range", &format!("bytes */{}", file.len));
+            request.respond(Response::empty(416).with_header(content_range))?;
+            return Ok((416, 0, false));
+        }
+    };
+
+    let len = end - start;
+    let mut headers = vec![
+        header("Accept-Ranges", "bytes"),
+        header("Content-Type", "application/octet-stream"),
+    ];
+    if status == 206 {
+        headers.push(header(
+            "Content-Range",
+            &format!("bytes {start}-{}/{}", end - 1, file.len),
+        ));
+    }
+    // tiny_http leaves the body out of the answer to a HEAD request.
+    let mut reader = fs::File::open(&file.path)?;
+    reader.seek(SeekFrom::Start(start))?;
+    request.respond(Response::new(
+        StatusCode(status),
+        headers,
+        reader.take(len),
+        Some(len as usize),
+        None,
+    ))?;
+    if head {
+        return Ok((status, 0, false));
+    }
+    Ok((status, len, end == file.len))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Range {
+    /// The half-open byte range `start..end`.
+    Bytes(u64, u64),
+    /// A range the file cannot satisfy.
+    Unsatisfiable,
+    /// A header that is not a single byte range; the whole file is sent.
+    Ignored,
+}
+
+/// Reads a `Range` header holding one byte range, as `bytes=<first>-<last>`,
+/// `bytes=<first>-` or `bytes=-<suffix length>`.
+fn parse_range(value: &str, len: u64) -> Range {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return Range::Ignored;
+    };
+    if spec.contains(',') {
+        return Range::Ignored;
+    }
+    let Some((first, last)) = spec.split_once('-') else {
+        return Range::Ignored;
+    };
+    let (first, last) = (first.trim(), last.trim());
+    let (start, end) = match (first.parse::<u64>(), last.parse::<u64>()) {
+        (Ok(first), Ok(last)) if first <= last => (first, last.saturating_add(1).min(len)),
+        (Ok(first), Err(_)) if last.is_empty() => (first, len),
+        (Err(_), Ok(suffix)) if first.is_empty() && suffix > 0 => (len.saturating_sub(suffix), len),
+        _ => return Range::Ignored,
+    };
+    if start >= len {
+        return Range::Unsatisfiable;
+    }
+    Range::Bytes(start, end)
+}
+
+/// The percent-decoded path of a request URL, without its query.
+fn url_path(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Percent-encodes every byte of `name` but the unreserved characters of
+/// RFC 3986.
+pub(crate) fn percent_encode(name: &str) -> String {
+    name.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("valid header")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_range_reads_closed_and_open_ranges() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Range::Bytes(0, 100));
+        assert_eq!(parse_range(" bytes=10-10 ", 1000), Range::Bytes(10, 11));
+        assert_eq!(parse_range("bytes=900-", 1000), Range::Bytes(900, 1000));
+        // the last byte is capped at the end of the file
+        assert_eq!(parse_range("bytes=900-5000", 1000), Range::Bytes(900, 1000));
+    }
+
+    #[test]
+    fn parse_range_reads_suffix_ranges() {
+        assert_eq!(parse_range("bytes=-100", 1000), Range::Bytes(900, 1000));
+        assert_eq!(parse_range("bytes=-5000", 1000), Range::Bytes(0, 1000));
+        assert_eq!(parse_range("bytes=-0", 1000), Range::Ignored);
+    }
+
+    #[test]
+    fn parse_range_rejects_starts_past_the_end() {
+        assert_eq!(parse_range("bytes=1000-", 1000), Range::Unsatisfiable);
+        assert_eq!(parse_range("bytes=1000-1200", 1000), Range::Unsatisfiable);
+        assert_eq!(parse_range("bytes=-10", 0), Range::Unsatisfiable);
+    }
+
+    #[test]
+    fn parse_range_ignores_other_headers() {
+        assert_eq!(parse_range("bytes=100-10", 1000), Range::Ignored);
+        assert_eq!(parse_range("bytes=0-1,5-9", 1000), Range::Ignored);
+        assert_eq!(parse_range("items=0-1", 1000), Range::Ignored);
+        assert_eq!(parse_range("bytes=5", 1000), Range::Ignored);
+        assert_eq!(parse_range("bytes=-", 1000), Range::Ignored);
+        assert_eq!(parse_range("bytes=a-b", 1000), Range::Ignored);
+    }
+
+    #[test]
+    fn url_path_drops_the_query_and_decodes() {
+        assert_eq!(url_path("/abc/input.json?x=1"), "/abc/input.json");
+        assert_eq!(url_path("/abc/my%20file.json"), "/abc/my file.json");
+        assert_eq!(url_path("/abc/100%25%2Bmore"), "/abc/100%+more");
+        // a stray % is kept as is
+        assert_eq!(url_path("/abc/50%off%2"), "/abc/50%off%2");
+    }
+
+    #[test]
+    fn add_encodes_the_name_in_the_url() {
+        let dir = std::env::temp_dir().join(format!("file-server-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("my input#1.json");
+        fs::write(&path, b"{}").unwrap();
+
+        let mut server = FileServer::new("http://127.0.0.1:8080/");
+        let (url, checksum) = server.add(&path).unwrap();
+        assert_eq!(
+            url,
+            format!("http://127.0.0.1:8080/{checksum}/my%20input%231.json")
+        );
+        let url_path = url_path(url.strip_prefix("http://127.0.0.1:8080").unwrap());
+        assert!(server.state.files.contains_key(&url_path));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,384 @@
+//! Runs a Gevulot workflow on the local machine, without Gevulot or OPS.
+//!
+//! The workflow is the `--tasks` JSON of `gevulot-cli exec` (see
+//! `run_task.tmpl`). Each step gets its own temporary directory as
+//! `/workspace`, its inputs are staged there, and the program binary is
+//! started in local mode (see [`crate::task`]). `Output` inputs are copied
+//! from the workspace of the earlier step that returned them, as Gevulot
+//! forwards the prover's files to the verifier.
+
+use crate::task::{LocalTask, TaskOutput, RESULT_ENV, TASK_ENV, WORKSPACE_ENV, WORKSPACE_PATH};
+use crate::tx::ProgramData;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CmdArg {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskInput {
+    Input {
+        /// The local file, or its checksum when `file_url` is set.
+        local_path: String,
+        vm_path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        file_url: Option<String>,
+    },
+    Output {
+        source_program: String,
+        file_name: String,
+    },
+}
+
+impl From<&ProgramData> for TaskInput {
+    fn from(input: &ProgramData) -> Self {
+        match input {
+            ProgramData::Input {
+                file_name,
+                file_url,
+                checksum,
+            } => TaskInput::Input {
+                local_path: checksum.clone(),
+                vm_path: file_name.clone(),
+                file_url: Some(file_url.clone()),
+            },
+            ProgramData::Output {
+                source_program,
+                file_name,
+            } => TaskInput::Output {
+                source_program: source_program.clone(),
+                file_name: file_name.clone(),
+            },
+        }
+    }
+}
+
+/// One step of the workflow, in the format of the `--tasks` array.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskStep {
+    pub program: String,
+    #[serde(default)]
+    pub cmd_args: Vec<CmdArg>,
+    #[serde(default)]
+    pub inputs: Vec<TaskInput>,
+}
+
+impl TaskStep {
+    /// The arguments the program receives, as the gevulot-cli flattens them.
+    pub fn args(&self) -> Vec<String> {
+        self.cmd_args
+            .iter()
+            .flat_map(|a| [a.name.clone(), a.value.clone()])
+            .collect()
+    }
+}
+
+pub fn parse_tasks(json: &str) -> Result<Vec<TaskStep>> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Parses a `KEY=PATH` command line value, as used for programs and mounts.
+pub fn parse_key_path(s: &str) -> std::result::Result<(String, PathBuf), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.to_string(), PathBuf::from(v)))
+        .ok_or_else(|| format!("expected KEY=PATH, got {s}"))
+}
+
+/// A step as the harness runs it, with the arguments already flattened, as
+/// in the `WorkflowStep` of a Run transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub program: String,
+    pub args: Vec<String>,
+    pub inputs: Vec<TaskInput>,
+}
+
+impl From<&TaskStep> for Step {
+    fn from(step: &TaskStep) -> Self {
+        Step {
+            program: step.program.clone(),
+            args: step.args(),
+            inputs: step.inputs.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StepResult {
+    pub program: String,
+    pub workspace: PathBuf,
+    pub data: Vec<u8>,
+    /// The returned files, as VM paths.
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Harness {
+    /// The binary to run for each program hash.
+    pub programs: HashMap<String, PathBuf>,
+    /// Host directories standing in for VM paths baked into the image, such
+    /// as `/gevulot`.
+    pub mounts: Vec<(String, PathBuf)>,
+    /// Where to find inputs given by `file_url`, by file name. The harness
+    /// never downloads: an input found neither there nor at a `file://` URL
+    /// is an error.
+    pub input_dir: Option<PathBuf>,
+    /// Where the step directories are created, under the system temporary
+    /// directory if `None`. They are kept for inspection until a later run
+    /// in the same directory replaces them.
+    pub work_dir: Option<PathBuf>,
+}
+
+impl Harness {
+    /// Runs the steps in order and returns the result of each.
+    pub fn run(&self, steps: &[Step]) -> Result<Vec<StepResult>> {
+        let root = self.root_dir()?;
+        log::info!("running {} steps in {}", steps.len(), root.display());
+
+        let mut results: Vec<StepResult> = Vec::with_capacity(steps.len());
+        for (i, step) in steps.iter().enumerate() {
+            let result = self.run_step(&root.join(format!("step-{i}")), i, step, &results)?;
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    /// The directory holding the step directories.
+    pub fn root_dir(&self) -> Result<PathBuf> {
+        let root = match &self.work_dir {
+            Some(dir) => {
+                fs::create_dir_all(dir)?;
+                dir.clone()
+            }
+            None => {
+                let dir =
+                    std::env::temp_dir().join(format!("gevulot-local-{}", std::process::id()));
+                fs::create_dir_all(&dir)?;
+                dir
+            }
+        };
+        Ok(fs::canonicalize(root)?)
+    }
+
+    /// Runs `step` in `dir`, taking its `Output` inputs from `previous`.
+    /// Whatever `dir` holds is removed first.
+    pub fn run_step(
+        &self,
+        dir: &Path,
+        index: usize,
+        step: &Step,
+        previous: &[StepResult],
+    ) -> Result<StepResult> {
+        let binary = self
+            .programs
+            .get(&step.program)
+            .ok_or_else(|| format!("no binary given for program {}", step.program))?;
+        // A directory left by an earlier run, with the same process id or
+        // work_dir, must not pass its outputs off as this step's.
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+        let workspace = dir.join("workspace");
+        fs::create_dir_all(&workspace)?;
+
+        for input in &step.inputs {
+            self.stage(&workspace, input, previous)?;
+        }
+
+        let task = LocalTask {
+            id: format!("local-{index}"),
+            args: step
+                .args
+                .iter()
+                .map(|a| self.map_path(a, &workspace))
+                .collect(),
+        };
+        let task_file = dir.join("task.json");
+        let result_file = dir.join("result.json");
+        fs::write(&task_file, serde_json::to_vec(&task)?)?;
+
+        log::info!("step {index}: {} {:?}", binary.display(), task.args);
+        let status = Command::new(binary)
+            .env(TASK_ENV, &task_file)
+            .env(RESULT_ENV, &result_file)
+            .env(WORKSPACE_ENV, &workspace)
+            .status()?;
+        if !status.success() {
+            return Err(format!("step {index} ({}) exited with {status}", step.program).into());
+        }
+
+        let output: TaskOutput = serde_json::from_slice(&fs::read(&result_file)?)?;
+        Ok(StepResult {
+            program: step.program.clone(),
+            data: output.data,
+            files: output
+                .files
+                .iter()
+                .map(|f| to_vm_path(f, &workspace))
+                .collect(),
+            workspace,
+        })
+    }
+
+    fn stage(&self, workspace: &Path, input: &TaskInput, previous: &[StepResult]) -> Result<()> {
+        match input {
+            TaskInput::Input {
+                local_path,
+                vm_path,
+                file_url: None,
+            } => copy(Path::new(local_path), &host_path(workspace, vm_path)?),
+            TaskInput::Input {
+                local_path: checksum,
+                vm_path,
+                file_url: Some(url),
+            } => {
+                let target = host_path(workspace, vm_path)?;
+                self.fetch(url, &target)?;
+                let actual = file_checksum(&target)?;
+                if &actual != checksum {
+                    return Err(format!(
+                        "checksum mismatch for {url}: expected {checksum}, got {actual}"
+                    )
+                    .into());
+                }
+                Ok(())
+            }
+            TaskInput::Output {
+                source_program,
+                file_name,
+            } => {
+                let source = previous
+                    .iter()
+                    .rev()
+                    .find(|r| &r.program == source_program)
+                    .ok_or_else(|| format!("no earlier step ran {source_program}"))?;
+                if !source.files.contains(file_name) {
+                    return Err(format!("{source_program} did not return {file_name}").into());
+                }
+                copy(
+                    &host_path(&source.workspace, file_name)?,
+                    &host_path(workspace, file_name)?,
+                )
+            }
+        }
+    }
+
+    /// Copies the file at `url` to `target`, from the input directory when
+    /// it holds a file of that name.
+    fn fetch(&self, url: &str, target: &Path) -> Result<()> {
+        if let Some(path) = url.strip_prefix("file://") {
+            return copy(Path::new(path), target);
+        }
+        let name = url.rsplit('/').next().unwrap_or_default();
+        if let Some(local) = self.input_dir.as_ref().map(|d| d.join(name)) {
+            if !name.is_empty() && local.is_file() {
+                return copy(&local, target);
+            }
+        }
+        let staged_in = match &self.input_dir {
+            Some(dir) => format!("{} has no file {name}", dir.display()),
+            None => "no input directory is given".to_string(),
+        };
+        Err(format!("input not staged: {url} is not a file:// URL and {staged_in}").into())
+    }
+
+    /// Rewrites an argument naming a VM path to the matching host path.
+    fn map_path(&self, arg: &str, workspace: &Path) -> String {
+        let mounts = self
+            .mounts
+            .iter()
+            .map(|(vm, host)| (vm.as_str(), host.as_path()))
+            .chain([(WORKSPACE_PATH, workspace)]);
+        for (vm, host) in mounts {
+            if let Some(rest) = strip_dir(arg, vm) {
+                return format!("{}{}", host.display(), rest);
+            }
+        }
+        arg.to_string()
+    }
+}
+
+/// Strips `dir` from the front of `path` when `path` is `dir` or below it.
+fn strip_dir<'a>(path: &'a str, dir: &str) -> Option<&'a str> {
+    let rest = path.strip_prefix(dir.trim_end_matches('/'))?;
+    (rest.is_empty() || rest.starts_with('/')).then_some(rest)
+}
+
+fn host_path(workspace: &Path, vm_path: &str) -> Result<PathBuf> {
+    let rest = strip_dir(vm_path, WORKSPACE_PATH)
+        .ok_or_else(|| format!("{vm_path} is not under {WORKSPACE_PATH}"))?;
+    Ok(workspace.join(rest.trim_start_matches('/')))
+}
+
+fn to_vm_path(file: &str, workspace: &Path) -> String {
+    match strip_dir(file, &workspace.display().to_string()) {
+        Some(rest) => format!("{WORKSPACE_PATH}{rest}"),
+        None => file.to_string(),
+    }
+}
+
+fn copy(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(from, to).map_err(|e| format!("copying {}: {e}", from.display()))?;
+    Ok(())
+}
+
+pub fn file_checksum(path: &Path) -> Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(fs::File::open(path)?)?;
+    Ok(hasher.finalize().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn steps_do_not_see_the_files_of_an_earlier_run() {
+        let dir = std::env::temp_dir().join(format!("harness-rerun-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // Returns proof.json only when the step wrote it in this run.
+        let program = dir.join("program.sh");
+        fs::write(
+            &program,
+            "#!/bin/sh\n\
+             if [ -f \"$EIGEN_GEVULOT_WORKSPACE/proof.json\" ]; then\n\
+             echo '{\"data\":[],\"files\":[\"/workspace/proof.json\"]}' > \"$EIGEN_GEVULOT_LOCAL_RESULT\"\n\
+             else\n\
+             echo '{\"data\":[],\"files\":[]}' > \"$EIGEN_GEVULOT_LOCAL_RESULT\"\n\
+             fi\n",
+        )
+        .unwrap();
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let step_dir = dir.join("runs").join("step-0");
+        fs::create_dir_all(step_dir.join("workspace")).unwrap();
+        fs::write(step_dir.join("workspace/proof.json"), "stale").unwrap();
+
+        let harness = Harness {
+            programs: HashMap::from([("prover".to_string(), program)]),
+            work_dir: Some(dir.join("runs")),
+            ..Default::default()
+        };
+        let step = Step {
+            program: "prover".to_string(),
+            args: vec![],
+            inputs: vec![],
+        };
+        let results = harness.run(&[step]).unwrap();
+        assert!(results[0].files.is_empty());
+        assert!(!step_dir.join("workspace/proof.json").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,416 @@
+//! Decodes the transactions of a workflow tree for `tx` and `tx-tree`: the
+//! outcome of a Proof and the verdict of a Verification are read from their
+//! base64 data, and each transaction gets a one-line status.
+//!
+//! The result is printed either as an indented tree, like `print-tx-tree`
+//! with the status of each transaction, or as JSON for scripts.
+
+use crate::outcome::{Outcome, Verdict};
+use crate::progress::{WorkflowProgress, WorkflowStatus};
+use crate::tx::{Payload, TxFile, TxOutput, TxTree, Workflow};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The result data of a Proof or a Verification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultData {
+    Outcome(Outcome),
+    Verdict(Verdict),
+    /// Data that is neither, as text when it is printable.
+    Text(String),
+    /// Data that is neither, in base64.
+    Base64(String),
+}
+
+impl ResultData {
+    fn decode_outcome(data: &[u8]) -> Self {
+        Outcome::from_bytes(data)
+            .map(ResultData::Outcome)
+            .unwrap_or_else(|_| Self::raw(data))
+    }
+
+    fn decode_verdict(data: &[u8]) -> Self {
+        Verdict::from_bytes(data)
+            .map(ResultData::Verdict)
+            .unwrap_or_else(|_| Self::raw(data))
+    }
+
+    fn raw(data: &[u8]) -> Self {
+        match std::str::from_utf8(data) {
+            Ok(text) if text.chars().all(|c| !c.is_control() || c.is_whitespace()) => {
+                ResultData::Text(text.to_string())
+            }
+            _ => ResultData::Base64(STANDARD.encode(data)),
+        }
+    }
+}
+
+/// A transaction with its result data decoded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Decoded {
+    Run {
+        workflow: Workflow,
+    },
+    Proof {
+        parent: String,
+        prover: String,
+        result: ResultData,
+        files: Vec<TxFile>,
+    },
+    Verification {
+        parent: String,
+        verifier: String,
+        result: ResultData,
+        files: Vec<TxFile>,
+    },
+    /// Any other transaction, with its payload as the node returns it.
+    Other {
+        payload: Payload,
+    },
+}
+
+impl Decoded {
+    pub fn new(payload: &Payload) -> Self {
+        match payload {
+            Payload::Run { workflow } => Decoded::Run {
+                workflow: workflow.clone(),
+            },
+            Payload::Proof {
+                parent,
+                prover,
+                proof,
+                files,
+            } => Decoded::Proof {
+                parent: parent.clone(),
+                prover: prover.clone(),
+                result: ResultData::decode_outcome(proof),
+                files: files.clone(),
+            },
+            Payload::Verification {
+                parent,
+                verifier,
+                verification,
+                files,
+            } => Decoded::Verification {
+                parent: parent.clone(),
+                verifier: verifier.clone(),
+                result: ResultData::decode_verdict(verification),
+                files: files.clone(),
+            },
+            payload => Decoded::Other {
+                payload: payload.clone(),
+            },
+        }
+    }
+}
+
+/// One transaction of a tree. `tx` is `None` when it could not be fetched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InspectedTx {
+    pub hash: String,
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx: Option<Decoded>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<InspectedTx>,
+}
+
+impl InspectedTx {
+    /// `tx` alone, without its children.
+    pub fn new(tx: &TxOutput) -> Self {
+        let decoded = Decoded::new(&tx.payload);
+        InspectedTx {
+            hash: tx.hash.clone(),
+            status: tx_status(&decoded),
+            author: Some(tx.author.clone()),
+            tx: Some(decoded),
+            children: vec![],
+        }
+    }
+
+    fn missing(hash: &str) -> Self {
+        InspectedTx {
+            hash: hash.to_string(),
+            status: "not fetched".to_string(),
+            author: None,
+            tx: None,
+            children: vec![],
+        }
+    }
+}
+
+/// A workflow tree with the status of the whole workflow.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InspectedTree {
+    pub status: String,
+    pub root: InspectedTx,
+}
+
+/// Decodes `tree` with the transactions fetched for it, by hash. A tree of
+/// `None` is a Run transaction the node has no result for yet.
+pub fn inspect_tree(
+    root: &str,
+    tree: Option<&TxTree>,
+    txs: &HashMap<String, TxOutput>,
+) -> InspectedTree {
+    // In tree order, so the last Proof and Verification of the tree count.
+    let mut progress = WorkflowProgress::default();
+    let hashes = tree.map_or_else(|| vec![root], TxTree::hashes);
+    for tx in hashes.into_iter().filter_map(|hash| txs.get(hash)) {
+        progress.record(tx.clone());
+    }
+    let root = match tree {
+        Some(tree) => inspect_node(tree, txs),
+        None => txs
+            .get(root)
+            .map(InspectedTx::new)
+            .unwrap_or_else(|| InspectedTx::missing(root)),
+    };
+    InspectedTree {
+        status: workflow_status(&progress.status()),
+        root,
+    }
+}
+
+fn inspect_node(tree: &TxTree, txs: &HashMap<String, TxOutput>) -> InspectedTx {
+    let mut node = txs
+        .get(tree.hash())
+        .map(InspectedTx::new)
+        .unwrap_or_else(|| InspectedTx::missing(tree.hash()));
+    node.children = tree
+        .children()
+        .iter()
+        .map(|child| inspect_node(child, txs))
+        .collect();
+    node
+}
+
+/// Renders the tree as `print-tx-tree` does, with the kind and status of
+/// each transaction.
+pub fn render_tree(tree: &InspectedTree) -> String {
+    let mut out = format!("Workflow: {}\n", tree.status);
+    render_node(&tree.root, 0, &mut out);
+    out
+}
+
+fn render_node(node: &InspectedTx, depth: usize, out: &mut String) {
+    let level = match (depth, node.children.is_empty()) {
+        (0, _) => "Root",
+        (_, true) => "Leaf",
+        (_, false) => "Node",
+    };
+    let kind = match &node.tx {
+        Some(Decoded::Run { .. }) => "Run",
+        Some(Decoded::Proof { .. }) => "Proof",
+        Some(Decoded::Verification { .. }) => "Verification",
+        Some(Decoded::Other { payload }) => payload.kind(),
+        None => "?",
+    };
+    out.push_str(&format!(
+        "{}{level}: {} {kind}: {}\n",
+        "\t".repeat(depth),
+        node.hash,
+        node.status
+    ));
+    for child in &node.children {
+        render_node(child, depth + 1, out);
+    }
+}
+
+/// Renders one transaction with its decoded result and files.
+pub fn render_tx(tx: &InspectedTx) -> String {
+    let mut out = format!("{}: {}\n", tx.hash, tx.status);
+    if let Some(author) = &tx.author {
+        out.push_str(&format!("  author: {author}\n"));
+    }
+    match &tx.tx {
+        Some(Decoded::Run { workflow }) => {
+            for (i, step) in workflow.steps.iter().enumerate() {
+                out.push_str(&format!(
+                    "  step {i}: program {} with {} args and {} inputs\n",
+                    step.program,
+                    step.args.len(),
+                    step.inputs.len()
+                ));
+            }
+        }
+        Some(Decoded::Proof {
+            parent,
+            prover,
+            result,
+            files,
+        }) => {
+            out.push_str(&format!("  parent: {parent}\n  prover: {prover}\n"));
+            render_result(result, &mut out);
+            render_files(files, &mut out);
+        }
+        Some(Decoded::Verification {
+            parent,
+            verifier,
+            result,
+            files,
+        }) => {
+            out.push_str(&format!("  parent: {parent}\n  verifier: {verifier}\n"));
+            render_result(result, &mut out);
+            render_files(files, &mut out);
+        }
+        Some(Decoded::Other { payload }) => out.push_str(&format!("  {}\n", payload.kind())),
+        None => {}
+    }
+    out
+}
+
+fn render_result(result: &ResultData, out: &mut String) {
+    match result {
+        ResultData::Verdict(verdict) if !verdict.publics.is_empty() => {
+            out.push_str(&format!("  publics: {}\n", verdict.publics.join(", ")));
+        }
+        ResultData::Text(text) => out.push_str(&format!("  data: {text}\n")),
+        ResultData::Base64(data) => out.push_str(&format!("  data (base64): {data}\n")),
+        _ => {}
+    }
+}
+
+fn render_files(files: &[TxFile], out: &mut String) {
+    for file in files {
+        out.push_str(&format!(
+            "  file {} {} {}\n",
+            file.vm_path, file.checksum, file.url
+        ));
+    }
+}
+
+/// The one-line status of a transaction.
+fn tx_status(tx: &Decoded) -> String {
+    match tx {
+        Decoded::Run { workflow } => format!("workflow of {} steps", workflow.steps.len()),
+        Decoded::Proof { result, .. } => match result {
+            ResultData::Outcome(outcome) if outcome.is_success() => "proved".to_string(),
+            ResultData::Outcome(outcome) => {
+                let phase = outcome
+                    .phase
+                    .as_ref()
+                    .map(|phase| format!(" in {phase}"))
+                    .unwrap_or_default();
+                format!(
+                    "prover failed{phase} (code {}): {}",
+                    outcome.code, outcome.message
+                )
+            }
+            _ => "proof data without an outcome".to_string(),
+        },
+        Decoded::Verification { result, .. } => match result {
+            ResultData::Verdict(verdict) if verdict.verified => {
+                format!("verified: {}", verdict.reason)
+            }
+            ResultData::Verdict(verdict) => format!("rejected: {}", verdict.reason),
+            _ => "verification data without a verdict".to_string(),
+        },
+        Decoded::Other { payload } => payload.kind().to_lowercase(),
+    }
+}
+
+fn workflow_status(status: &WorkflowStatus) -> String {
+    match status {
+        WorkflowStatus::Pending => "pending".to_string(),
+        WorkflowStatus::Proved => "proved, waiting for the verifier".to_string(),
+        WorkflowStatus::Verified => "verified".to_string(),
+        WorkflowStatus::Rejected(reason) => format!("rejected: {reason}"),
+        WorkflowStatus::TimedOut => "timed out".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outcome::Status;
+
+    fn tx(hash: &str, payload: Payload) -> TxOutput {
+        TxOutput {
+            author: "author".to_string(),
+            hash: hash.to_string(),
+            payload,
+            nonce: 0,
+            signature: String::new(),
+        }
+    }
+
+    fn run() -> Payload {
+        Payload::Run {
+            workflow: Workflow { steps: vec![] },
+        }
+    }
+
+    fn proof(outcome: &Outcome) -> Payload {
+        Payload::Proof {
+            parent: "run".to_string(),
+            prover: "prover".to_string(),
+            proof: outcome.to_bytes(),
+            files: vec![],
+        }
+    }
+
+    #[test]
+    fn results_are_decoded_or_kept_as_raw_data() {
+        let failed = Outcome::failure(Status::ProverFailed, "boom").in_phase("witness");
+        let inspected = InspectedTx::new(&tx("p", proof(&failed)));
+        assert_eq!(inspected.status, "prover failed in witness (code 2): boom");
+
+        let text = Payload::Proof {
+            parent: String::new(),
+            prover: String::new(),
+            proof: b"not json".to_vec(),
+            files: vec![],
+        };
+        let inspected = InspectedTx::new(&tx("p", text));
+        assert_eq!(inspected.status, "proof data without an outcome");
+        assert!(matches!(
+            inspected.tx,
+            Some(Decoded::Proof { result: ResultData::Text(ref text), .. }) if text == "not json"
+        ));
+
+        let binary = Payload::Verification {
+            parent: String::new(),
+            verifier: String::new(),
+            verification: vec![0, 159],
+            files: vec![],
+        };
+        let inspected = InspectedTx::new(&tx("v", binary));
+        assert!(matches!(
+            inspected.tx,
+            Some(Decoded::Verification { result: ResultData::Base64(ref data), .. }) if data == "AJ8="
+        ));
+    }
+
+    #[test]
+    fn tree_shows_each_transaction_and_the_workflow_status() {
+        let tree = TxTree::Root {
+            hash: "run".to_string(),
+            children: vec![TxTree::Node {
+                hash: "proof".to_string(),
+                children: vec![TxTree::Leaf {
+                    hash: "verification".to_string(),
+                }],
+            }],
+        };
+        let txs: HashMap<_, _> = [
+            ("run".to_string(), tx("run", run())),
+            ("proof".to_string(), tx("proof", proof(&Outcome::success()))),
+        ]
+        .into_iter()
+        .collect();
+        let inspected = inspect_tree("run", Some(&tree), &txs);
+        assert_eq!(
+            render_tree(&inspected),
+            "Workflow: proved, waiting for the verifier\n\
+             Root: run Run: workflow of 0 steps\n\
+             \tNode: proof Proof: proved\n\
+             \t\tLeaf: verification ?: not fetched\n"
+        );
+    }
+}
@@ -0,0 +1,21 @@
+pub mod args;
+pub mod bootloader;
+pub mod chunks;
+pub mod deployment;
+pub mod download;
+pub mod fanout;
+pub mod file_server;
+pub mod harness;
+pub mod inspect;
+pub mod mock_node;
+pub mod outcome;
+pub mod profile;
+pub mod progress;
+pub mod registry;
+pub mod report;
+pub mod resources;
+pub mod staging;
+pub mod stark;
+pub mod task;
+pub mod tx;
+pub mod workflow;
@@ -0,0 +1,304 @@
+//! A stand-in for a Gevulot node, for testing the client without a network.
+//!
+//! It serves the JSON-RPC subset the client uses (`sendTransaction`,
+//! `getTransaction` and `getTransactionTree`) over HTTP, records every
+//! transaction, remembers deployed programs, and runs the workflow of each
+//! Run transaction through the local [`Harness`]. The result of the first
+//! step becomes a Proof transaction under the Run, the results of the later
+//! steps Verification transactions under the Proof, and their files are
+//! served under `/txfiles/` as the node does.
+
+use crate::harness::{file_checksum, Harness, Step, StepResult, TaskInput};
+use crate::task::WORKSPACE_PATH;
+use crate::tx::{Payload, TxFile, TxOutput, TxTree, Workflow};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const AUTHOR: &str = "mock-node";
+
+#[derive(Debug, Default)]
+struct State {
+    txs: HashMap<String, TxOutput>,
+    /// Proof transactions by Run transaction.
+    proofs: HashMap<String, Vec<String>>,
+    /// Verification transactions by Proof transaction.
+    verifications: HashMap<String, Vec<String>>,
+    /// Deployed program names by program hash.
+    deployed: HashMap<String, String>,
+    /// Result files by URL path.
+    files: HashMap<String, PathBuf>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MockNode {
+    /// The binary of each program, by program hash or deployed name.
+    pub programs: HashMap<String, PathBuf>,
+    /// See [`Harness::mounts`].
+    pub mounts: Vec<(String, PathBuf)>,
+    /// See [`Harness::input_dir`].
+    pub input_dir: Option<PathBuf>,
+    /// Where each Run transaction gets a directory for its steps.
+    pub work_dir: PathBuf,
+    /// The base of the result file URLs, eg. `http://127.0.0.1:9944`.
+    pub public_url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockNode {
+    pub fn new(work_dir: PathBuf, public_url: String) -> Self {
+        MockNode {
+            work_dir,
+            public_url,
+            ..Default::default()
+        }
+    }
+
+    /// Serves requests on `addr` until the process ends.
+    pub fn serve(&self, addr: &str) -> Result<()> {
+        let server = Server::http(addr).map_err(|e| format!("binding {addr}: {e}"))?;
+        log::info!("mock node listening on {addr}");
+        for request in server.incoming_requests() {
+            if let Err(e) = self.handle(request) {
+                log::warn!("request failed: {e}");
+            }
+        }
+        Ok(())
+    }
+
+    fn handle(&self, mut request: Request) -> Result<()> {
+        match request.method() {
+            Method::Post => {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body)?;
+                let response = self.handle_rpc(&body);
+                let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+                    .expect("static header");
+                request.respond(Response::from_string(response.to_string()).with_header(header))?;
+            }
+            Method::Get => {
+                let file = self.state.lock().unwrap().files.get(request.url()).cloned();
+                match file {
+                    Some(path) => request.respond(Response::from_file(fs::File::open(path)?))?,
+                    None => request.respond(Response::empty(404))?,
+                }
+            }
+            _ => request.respond(Response::empty(405))?,
+        }
+        Ok(())
+    }
+
+    /// Answers one JSON-RPC 2.0 request. The results are shaped like the
+    /// node's `RpcResponse`: `{"Ok": ...}` or `{"Err": {...}}`.
+    pub fn handle_rpc(&self, body: &str) -> Value {
+        let request: Value = match serde_json::from_str(body) {
+            Ok(request) => request,
+            Err(e) => return rpc_error(Value::Null, -32700, &e.to_string()),
+        };
+        let id = request["id"].clone();
+        let param = match &request["params"] {
+            Value::Array(params) => params.first().cloned().unwrap_or(Value::Null),
+            other => other.clone(),
+        };
+
+        let result = match request["method"].as_str().unwrap_or_default() {
+            "sendTransaction" => self.send_transaction(param),
+            "getTransaction" => self.get_transaction(param.as_str().unwrap_or_default()),
+            "getTransactionTree" => self.get_tx_tree(param.as_str().unwrap_or_default()),
+            method => return rpc_error(id, -32601, &format!("method not found: {method}")),
+        };
+        json!({ "jsonrpc": "2.0", "id": id, "result": result })
+    }
+
+    fn send_transaction(&self, tx: Value) -> Value {
+        let tx = match parse_transaction(&tx) {
+            Ok(tx) => tx,
+            Err(e) => return json!({ "Err": { "InvalidRequest": e.to_string() } }),
+        };
+        log::info!("received {} transaction {}", tx.payload.kind(), tx.hash);
+
+        let mut state = self.state.lock().unwrap();
+        match &tx.payload {
+            Payload::Deploy {
+                prover, verifier, ..
+            } => {
+                for program in [prover, verifier] {
+                    state
+                        .deployed
+                        .insert(program.hash.clone(), program.name.clone());
+                }
+            }
+            Payload::Run { workflow } => {
+                let node = self.clone();
+                let (hash, workflow) = (tx.hash.clone(), workflow.clone());
+                std::thread::spawn(move || {
+                    if let Err(e) = node.run_workflow(&hash, &workflow) {
+                        log::warn!("workflow of {hash} failed: {e}");
+                    }
+                });
+            }
+            _ => {}
+        }
+        state.txs.insert(tx.hash.clone(), tx);
+        json!({ "Ok": null })
+    }
+
+    fn get_transaction(&self, hash: &str) -> Value {
+        match self.state.lock().unwrap().txs.get(hash) {
+            Some(tx) => json!({ "Ok": tx }),
+            None => json!({ "Err": { "NotFound": format!("no tx found for {hash}") } }),
+        }
+    }
+
+    fn get_tx_tree(&self, hash: &str) -> Value {
+        let state = self.state.lock().unwrap();
+        let Some(proofs) = state.proofs.get(hash) else {
+            return json!({ "Err": { "NotFound": format!("no root tx found for {hash}") } });
+        };
+        let children = proofs
+            .iter()
+            .map(|proof| match state.verifications.get(proof) {
+                Some(verifications) => TxTree::Node {
+                    children: verifications
+                        .iter()
+                        .map(|v| TxTree::Leaf { hash: v.clone() })
+                        .collect(),
+                    hash: proof.clone(),
+                },
+                None => TxTree::Leaf {
+                    hash: proof.clone(),
+                },
+            })
+            .collect();
+        json!({ "Ok": TxTree::Root { children, hash: hash.to_string() } })
+    }
+
+    /// Runs the steps of a workflow and records a transaction per step, as
+    /// far as the steps succeed.
+    fn run_workflow(&self, run_hash: &str, workflow: &Workflow) -> Result<()> {
+        let harness = Harness {
+            programs: self.binaries(),
+            mounts: self.mounts.clone(),
+            input_dir: self.input_dir.clone(),
+            work_dir: Some(self.work_dir.join(run_hash)),
+        };
+        let root = harness.root_dir()?;
+
+        let mut results: Vec<StepResult> = Vec::new();
+        let mut proof_hash = None;
+        for (i, step) in workflow.steps.iter().enumerate() {
+            let step = Step {
+                program: step.program.clone(),
+                args: step.args.clone(),
+                inputs: step.inputs.iter().map(TaskInput::from).collect(),
+            };
+            let result = harness.run_step(&root.join(format!("step-{i}")), i, &step, &results)?;
+
+            let parent = proof_hash.clone().unwrap_or_else(|| run_hash.to_string());
+            let hash =
+                blake3::hash(format!("{parent}:{i}:{}", step.program).as_bytes()).to_string();
+            let files = self.publish(&hash, &result)?;
+            let payload = match proof_hash {
+                None => Payload::Proof {
+                    parent: parent.clone(),
+                    prover: step.program.clone(),
+                    proof: result.data.clone(),
+                    files,
+                },
+                Some(_) => Payload::Verification {
+                    parent: parent.clone(),
+                    verifier: step.program.clone(),
+                    verification: result.data.clone(),
+                    files,
+                },
+            };
+
+            let mut state = self.state.lock().unwrap();
+            match proof_hash {
+                None => state.proofs.entry(parent).or_default().push(hash.clone()),
+                Some(_) => state
+                    .verifications
+                    .entry(parent)
+                    .or_default()
+                    .push(hash.clone()),
+            }
+            log::info!("{} transaction {hash} recorded", payload.kind());
+            state.txs.insert(
+                hash.clone(),
+                TxOutput {
+                    author: AUTHOR.to_string(),
+                    hash: hash.clone(),
+                    payload,
+                    nonce: 0,
+                    signature: String::new(),
+                },
+            );
+            drop(state);
+
+            proof_hash.get_or_insert(hash);
+            results.push(result);
+        }
+        Ok(())
+    }
+
+    /// The binaries by program hash, resolving deployed names.
+    fn binaries(&self) -> HashMap<String, PathBuf> {
+        let mut binaries = self.programs.clone();
+        for (hash, name) in &self.state.lock().unwrap().deployed {
+            if let Some(binary) = self.programs.get(name) {
+                binaries.insert(hash.clone(), binary.clone());
+            }
+        }
+        binaries
+    }
+
+    /// Serves the files of a step result under `/txfiles/<tx>/<checksum>/`.
+    fn publish(&self, tx_hash: &str, result: &StepResult) -> Result<Vec<TxFile>> {
+        let mut files = Vec::with_capacity(result.files.len());
+        for vm_path in &result.files {
+            let relative = vm_path
+                .strip_prefix(WORKSPACE_PATH)
+                .unwrap_or(vm_path)
+                .trim_start_matches('/');
+            let host = result.workspace.join(relative);
+            let checksum = file_checksum(&host)?;
+            let name = relative.rsplit('/').next().unwrap_or(relative);
+            let path = format!("/txfiles/{tx_hash}/{checksum}/{name}");
+
+            self.state.lock().unwrap().files.insert(path.clone(), host);
+            files.push(TxFile {
+                url: format!("{}{}", self.public_url.trim_end_matches('/'), path),
+                checksum,
+                vm_path: vm_path.clone(),
+            });
+        }
+        Ok(files)
+    }
+}
+
+/// Reads a signed transaction as sent by the client. Only the hash and the
+/// payload are used; the signature is not checked.
+fn parse_transaction(tx: &Value) -> Result<TxOutput> {
+    let hash = tx["hash"].as_str().ok_or("transaction without hash")?;
+    let text = |v: &Value| {
+        v.as_str()
+            .map(String::from)
+            .unwrap_or_else(|| v.to_string())
+    };
+    Ok(TxOutput {
+        author: text(&tx["author"]),
+        hash: hash.to_string(),
+        payload: serde_json::from_value(tx["payload"].clone())?,
+        nonce: tx["nonce"].as_u64().unwrap_or_default(),
+        signature: text(&tx["signature"]),
+    })
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
@@ -0,0 +1,111 @@
+//! The data a prover or a verifier returns in its `TaskResult`.
+//!
+//! Gevulot only hands the client the result data and the output files, so the
+//! status of the run is encoded here instead of only in `test.log`.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Success,
+    /// The arguments of the task could not be parsed.
+    InvalidArguments,
+    /// The proving function returned an error.
+    ProverFailed,
+    /// Proving returned without error, but a declared output file is missing.
+    MissingOutput,
+    /// The VM has less memory than the circuit is estimated to need.
+    InsufficientMemory,
+    /// An input file is malformed or belongs to another task.
+    InvalidInput,
+    /// The STARK parameters are below the requested security level.
+    InsufficientSecurity,
+}
+
+impl Status {
+    /// The machine-readable status code, `0` for success.
+    pub fn code(self) -> u32 {
+        match self {
+            Status::Success => 0,
+            Status::InvalidArguments => 1,
+            Status::ProverFailed => 2,
+            Status::MissingOutput => 3,
+            Status::InsufficientMemory => 4,
+            Status::InvalidInput => 5,
+            Status::InsufficientSecurity => 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Outcome {
+    pub status: Status,
+    pub code: u32,
+    pub message: String,
+    /// The phase of the run the failure happened in, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase: Option<String>,
+}
+
+impl Outcome {
+    pub fn success() -> Self {
+        Self::new(Status::Success, "the prover executes successfully")
+    }
+
+    pub fn failure(status: Status, message: impl Into<String>) -> Self {
+        Self::new(status, message)
+    }
+
+    pub fn in_phase(mut self, phase: impl Into<String>) -> Self {
+        self.phase = Some(phase.into());
+        self
+    }
+
+    fn new(status: Status, message: impl Into<String>) -> Self {
+        Outcome {
+            status,
+            code: status.code(),
+            message: message.into(),
+            phase: None,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.status == Status::Success
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("Outcome is always serializable")
+    }
+
+    pub fn from_bytes(data: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(data)
+    }
+}
+
+/// Splits `files` into the ones present on disk and the ones missing.
+pub fn split_existing(files: &[String]) -> (Vec<String>, Vec<String>) {
+    files.iter().cloned().partition(|f| Path::new(f).exists())
+}
+
+/// The data a verifier returns in its `TaskResult`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Verdict {
+    pub verified: bool,
+    pub reason: String,
+    /// The public outputs of the verified proof, when the verifier reports them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub publics: Vec<String>,
+}
+
+impl Verdict {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("Verdict is always serializable")
+    }
+
+    pub fn from_bytes(data: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(data)
+    }
+}
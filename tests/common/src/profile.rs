@@ -0,0 +1,179 @@
+//! Named profiles of the client: the node JSON-RPC URL, the key file and the
+//! base URL of the input file server of an environment.
+//!
+//! The profiles are kept in one JSON file:
+//!
+//! ```json
+//! {
+//!   "default": "devnet",
+//!   "profiles": {
+//!     "devnet": {
+//!       "json_rpc_url": "http://api.devnet.gevulot.com:9944",
+//!       "key_file": "my-local-key.pki",
+//!       "file_server": "http://4.145.88.10:8080"
+//!     },
+//!     "local-mock": {"json_rpc_url": "http://127.0.0.1:9944", "key_file": "localkey.pki"}
+//!   }
+//! }
+//! ```
+//!
+//! A setting is taken from the command line first, then from the
+//! `EIGEN_GEVULOT_*` environment variables, then from the selected profile,
+//! and falls back to the defaults below.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The profile file read when none is given.
+pub const DEFAULT_PROFILES_FILE: &str = "profiles.json";
+pub const DEFAULT_JSON_RPC_URL: &str = "http://localhost:9944";
+pub const DEFAULT_KEY_FILE: &str = "localkey.pki";
+
+pub const PROFILES_FILE_ENV: &str = "EIGEN_GEVULOT_PROFILES";
+pub const PROFILE_ENV: &str = "EIGEN_GEVULOT_PROFILE";
+pub const JSON_RPC_URL_ENV: &str = "EIGEN_GEVULOT_RPC_URL";
+pub const KEY_FILE_ENV: &str = "EIGEN_GEVULOT_KEY_FILE";
+pub const FILE_SERVER_ENV: &str = "EIGEN_GEVULOT_FILE_SERVER";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_rpc_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_server: Option<String>,
+}
+
+impl Profile {
+    /// The settings given by the environment variables, as read by `var`.
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let var = |name| var(name).filter(|v: &String| !v.is_empty());
+        Profile {
+            json_rpc_url: var(JSON_RPC_URL_ENV),
+            key_file: var(KEY_FILE_ENV).map(PathBuf::from),
+            file_server: var(FILE_SERVER_ENV),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// `self` with the settings `other` has replacing its own.
+    pub fn overridden_by(self, other: Profile) -> Self {
+        Profile {
+            json_rpc_url: other.json_rpc_url.or(self.json_rpc_url),
+            key_file: other.key_file.or(self.key_file),
+            file_server: other.file_server.or(self.file_server),
+        }
+    }
+
+    pub fn json_rpc_url(&self) -> String {
+        self.json_rpc_url
+            .clone()
+            .unwrap_or_else(|| DEFAULT_JSON_RPC_URL.to_string())
+    }
+
+    pub fn key_file(&self) -> PathBuf {
+        self.key_file
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_KEY_FILE))
+    }
+
+    /// The file server, or an error naming the ways to set it.
+    pub fn file_server(&self) -> Result<String> {
+        self.file_server.clone().ok_or_else(|| {
+            format!(
+                "no file server: pass it, set {FILE_SERVER_ENV} or use a profile with file_server"
+            )
+            .into()
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileFile {
+    /// The profile used when none is named.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl ProfileFile {
+    /// Reads a profile file. Relative key files are taken from its directory.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut file: ProfileFile = serde_json::from_slice(&fs::read(path)?)
+            .map_err(|e| format!("parsing {}: {e}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        for profile in file.profiles.values_mut() {
+            if let Some(key_file) = &mut profile.key_file {
+                *key_file = base.join(&*key_file);
+            }
+        }
+        Ok(file)
+    }
+
+    /// The profile `name`, or the default one when no name is given.
+    pub fn select(&self, name: Option<&str>) -> Result<Profile> {
+        let Some(name) = name.or(self.default.as_deref()) else {
+            return Ok(Profile::default());
+        };
+        self.profiles.get(name).cloned().ok_or_else(|| {
+            if self.profiles.is_empty() {
+                return format!("unknown profile {name}: no profiles are defined").into();
+            }
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            format!(
+                "unknown profile {name}, the profiles are: {}",
+                known.join(", ")
+            )
+            .into()
+        })
+    }
+}
+
+/// `value` quoted for a POSIX shell, so that `eval` reads it back verbatim.
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// The settings of the client: the profile `name` of the profile file,
+/// overridden by the environment, then by `cli`. The profile and its file
+/// are taken from the environment when not given, and a missing default
+/// profile file stands for no profiles at all.
+pub fn resolve(path: Option<&Path>, name: Option<&str>, cli: Profile) -> Result<Profile> {
+    let env_path = std::env::var(PROFILES_FILE_ENV).ok().map(PathBuf::from);
+    let env_name = std::env::var(PROFILE_ENV).ok().filter(|n| !n.is_empty());
+    let name = name.or(env_name.as_deref());
+
+    let path = path.map(Path::to_path_buf).or(env_path);
+    let file = match &path {
+        Some(path) => ProfileFile::load(path)?,
+        None if Path::new(DEFAULT_PROFILES_FILE).exists() => {
+            ProfileFile::load(Path::new(DEFAULT_PROFILES_FILE))?
+        }
+        None => ProfileFile::default(),
+    };
+    Ok(file
+        .select(name)?
+        .overridden_by(Profile::from_env())
+        .overridden_by(cli))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shell_quote_keeps_spaces_and_metacharacters() {
+        assert_eq!(shell_quote("localkey.pki"), "'localkey.pki'");
+        assert_eq!(shell_quote("my keys/$HOME;`id`"), "'my keys/$HOME;`id`'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote(""), "''");
+    }
+}
@@ -0,0 +1,162 @@
+//! The progress of a submitted workflow, read from its transaction tree.
+//!
+//! Gevulot records the prover's result as a Proof transaction under the Run
+//! transaction, and the verifier's as a Verification transaction under the
+//! Proof. Until the first of them exists the node answers "no root tx found".
+
+use crate::outcome::{Outcome, Verdict};
+use crate::tx::{Payload, TxOutput};
+
+/// The error text of the node for a Run transaction without results yet.
+pub const NO_ROOT_TX: &str = "no root tx found";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkflowStatus {
+    /// No result has been recorded yet.
+    Pending,
+    /// The prover returned, the verifier has not.
+    Proved,
+    /// The verifier accepted the proof.
+    Verified,
+    /// The verifier returned without accepting the proof, for the given reason.
+    Rejected(String),
+    /// The deadline passed before the verifier returned.
+    TimedOut,
+}
+
+impl WorkflowStatus {
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            WorkflowStatus::Verified | WorkflowStatus::Rejected(_) | WorkflowStatus::TimedOut
+        )
+    }
+
+    /// The process exit code for the status: 0 verified, 1 rejected, 2 timed out.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            WorkflowStatus::Verified => 0,
+            WorkflowStatus::Rejected(_) => 1,
+            WorkflowStatus::Pending | WorkflowStatus::Proved | WorkflowStatus::TimedOut => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WorkflowProgress {
+    pub proof: Option<TxOutput>,
+    pub verification: Option<TxOutput>,
+}
+
+impl WorkflowProgress {
+    /// Records `tx` if it is a Proof or a Verification transaction.
+    pub fn record(&mut self, tx: TxOutput) {
+        match tx.payload {
+            Payload::Proof { .. } => self.proof = Some(tx),
+            Payload::Verification { .. } => self.verification = Some(tx),
+            _ => {}
+        }
+    }
+
+    /// The outcome the prover returned, when it is one.
+    pub fn proof_outcome(&self) -> Option<Outcome> {
+        match &self.proof.as_ref()?.payload {
+            Payload::Proof { proof, .. } => Outcome::from_bytes(proof).ok(),
+            _ => None,
+        }
+    }
+
+    /// The verdict the verifier returned, when it is one.
+    pub fn verdict(&self) -> Option<Verdict> {
+        match &self.verification.as_ref()?.payload {
+            Payload::Verification { verification, .. } => Verdict::from_bytes(verification).ok(),
+            _ => None,
+        }
+    }
+
+    /// The status so far. A failed prover rejects the workflow at once,
+    /// whether or not a Verification has been recorded.
+    pub fn status(&self) -> WorkflowStatus {
+        if let Some(outcome) = self.proof_outcome().filter(|o| !o.is_success()) {
+            let phase = outcome
+                .phase
+                .map(|phase| format!(" in {phase}"))
+                .unwrap_or_default();
+            return WorkflowStatus::Rejected(format!(
+                "the prover failed{phase}: {}",
+                outcome.message
+            ));
+        }
+        if self.verification.is_none() {
+            return match self.proof {
+                Some(_) => WorkflowStatus::Proved,
+                None => WorkflowStatus::Pending,
+            };
+        }
+        match self.verdict() {
+            Some(verdict) if verdict.verified => WorkflowStatus::Verified,
+            Some(verdict) => WorkflowStatus::Rejected(verdict.reason),
+            None => WorkflowStatus::Rejected("the verification data is not a verdict".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outcome::Status;
+
+    fn tx(payload: Payload) -> TxOutput {
+        TxOutput {
+            author: String::new(),
+            hash: String::new(),
+            payload,
+            nonce: 0,
+            signature: String::new(),
+        }
+    }
+
+    fn proof(outcome: Outcome) -> TxOutput {
+        tx(Payload::Proof {
+            parent: String::new(),
+            prover: String::new(),
+            proof: outcome.to_bytes(),
+            files: vec![],
+        })
+    }
+
+    #[test]
+    fn failed_prover_rejects_without_a_verification() {
+        let mut progress = WorkflowProgress::default();
+        assert_eq!(progress.status(), WorkflowStatus::Pending);
+        let outcome = Outcome::failure(Status::ProverFailed, "boom").in_phase("witness");
+        progress.record(proof(outcome));
+        let status = progress.status();
+        assert_eq!(
+            status,
+            WorkflowStatus::Rejected("the prover failed in witness: boom".to_string())
+        );
+        assert!(status.is_final());
+        assert_eq!(status.exit_code(), 1);
+    }
+
+    #[test]
+    fn successful_prover_waits_for_the_verifier() {
+        let mut progress = WorkflowProgress::default();
+        progress.record(proof(Outcome::success()));
+        assert_eq!(progress.status(), WorkflowStatus::Proved);
+
+        let verdict = Verdict {
+            verified: true,
+            reason: "the STARK proof is valid".to_string(),
+            publics: Vec::new(),
+        };
+        progress.record(tx(Payload::Verification {
+            parent: String::new(),
+            verifier: String::new(),
+            verification: verdict.to_bytes(),
+            files: vec![],
+        }));
+        assert_eq!(progress.status(), WorkflowStatus::Verified);
+    }
+}
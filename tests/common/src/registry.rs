@@ -0,0 +1,301 @@
+//! The local registry of deployed programs, so job files and `exec` tasks
+//! can name a program instead of copying its hash.
+//!
+//! Every deployment of a program name gets the next version. A program is
+//! referenced as `<name>@<version>`, `<name>@latest` or `<name>`, which is
+//! the promoted version of the name; the first version of a name is promoted
+//! when it is registered, later ones with `promote`. Program hashes are
+//! passed through as they are.
+
+use crate::tx::{ProgramMetadata, ResourceRequest};
+use crate::workflow::{is_hash, Job};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The registry file used when none is given.
+pub const DEFAULT_REGISTRY_FILE: &str = "registry.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisteredProgram {
+    pub name: String,
+    pub version: u32,
+    pub hash: String,
+    pub image_file_url: String,
+    pub image_file_checksum: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_requirements: Option<ResourceRequest>,
+    /// The hash of the Deploy transaction.
+    pub tx_hash: String,
+    /// Seconds since the Unix epoch.
+    pub deployed_at: u64,
+}
+
+impl RegisteredProgram {
+    pub fn reference(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Registry {
+    /// The promoted version of each name.
+    #[serde(default)]
+    pub promoted: BTreeMap<String, u32>,
+    #[serde(default)]
+    pub programs: Vec<RegisteredProgram>,
+}
+
+impl Registry {
+    /// Reads a registry file; a missing file is an empty registry.
+    pub fn load_or_default(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Registry::default());
+        }
+        Ok(serde_json::from_slice(&fs::read(path)?)
+            .map_err(|e| format!("parsing {}: {e}", path.display()))?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Records a program deployed by `tx_hash` under the next version of its
+    /// name. A program already registered under its name keeps its version.
+    pub fn register(&mut self, program: &ProgramMetadata, tx_hash: &str) -> &RegisteredProgram {
+        let existing = self
+            .programs
+            .iter()
+            .position(|p| p.name == program.name && p.hash == program.hash);
+        let index = match existing {
+            Some(index) => index,
+            None => {
+                let version = self.latest(&program.name).map_or(1, |p| p.version + 1);
+                self.programs.push(RegisteredProgram {
+                    name: program.name.clone(),
+                    version,
+                    hash: program.hash.clone(),
+                    image_file_url: program.image_file_url.clone(),
+                    image_file_checksum: program.image_file_checksum.clone(),
+                    resource_requirements: program.resource_requirements.clone(),
+                    tx_hash: tx_hash.to_string(),
+                    deployed_at: now(),
+                });
+                self.programs.len() - 1
+            }
+        };
+        self.promoted
+            .entry(program.name.clone())
+            .or_insert(self.programs[index].version);
+        &self.programs[index]
+    }
+
+    /// The programs of `name`, or all of them, by name and version.
+    pub fn list(&self, name: Option<&str>) -> Vec<&RegisteredProgram> {
+        let mut programs: Vec<_> = self
+            .programs
+            .iter()
+            .filter(|p| name.is_none() || name == Some(p.name.as_str()))
+            .collect();
+        programs.sort_by(|a, b| (&a.name, a.version).cmp(&(&b.name, b.version)));
+        programs
+    }
+
+    pub fn is_promoted(&self, program: &RegisteredProgram) -> bool {
+        self.promoted.get(&program.name) == Some(&program.version)
+    }
+
+    /// The program a `<name>[@<version>|@latest]` reference names.
+    pub fn find(&self, reference: &str) -> Result<&RegisteredProgram> {
+        let (name, version) = match reference.rsplit_once('@') {
+            Some((name, "latest")) => return self.latest(name).ok_or_else(|| unknown(name)),
+            Some((name, version)) => {
+                let version = version
+                    .parse::<u32>()
+                    .map_err(|_| format!("{reference}: the version is not a number"))?;
+                (name, version)
+            }
+            None => {
+                let version = self
+                    .promoted
+                    .get(reference)
+                    .ok_or_else(|| unknown(reference))?;
+                (reference, *version)
+            }
+        };
+        self.programs
+            .iter()
+            .find(|p| p.name == name && p.version == version)
+            .ok_or_else(|| format!("{name} has no version {version} in the registry").into())
+    }
+
+    /// Makes the program `reference` names the one its bare name resolves to.
+    pub fn promote(&mut self, reference: &str) -> Result<RegisteredProgram> {
+        let program = self.find(reference)?.clone();
+        self.promoted.insert(program.name.clone(), program.version);
+        Ok(program)
+    }
+
+    /// The hash of `program`: a program hash as it is, a reference looked up.
+    pub fn resolve(&self, program: &str) -> Result<String> {
+        if is_hash(program) {
+            return Ok(program.to_string());
+        }
+        Ok(self.find(program)?.hash.clone())
+    }
+
+    /// Replaces the program references of a job with their hashes.
+    pub fn resolve_job(&self, job: &mut Job) -> Result<()> {
+        for step in [&mut job.prover, &mut job.verifier] {
+            step.program = self.resolve(&step.program)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the `program` and `source_program` references of the task
+    /// array of `gevulot-cli exec --tasks` with their hashes.
+    pub fn resolve_tasks(&self, tasks: &str) -> Result<String> {
+        let mut tasks: serde_json::Value =
+            serde_json::from_str(tasks).map_err(|e| format!("parsing the tasks: {e}"))?;
+        self.resolve_value(&mut tasks)?;
+        Ok(tasks.to_string())
+    }
+
+    fn resolve_value(&self, value: &mut serde_json::Value) -> Result<()> {
+        match value {
+            serde_json::Value::Array(values) => {
+                for value in values {
+                    self.resolve_value(value)?;
+                }
+            }
+            serde_json::Value::Object(fields) => {
+                for (key, value) in fields {
+                    match value {
+                        serde_json::Value::String(program)
+                            if key == "program" || key == "source_program" =>
+                        {
+                            *program = self.resolve(program)?;
+                        }
+                        value => self.resolve_value(value)?,
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn latest(&self, name: &str) -> Option<&RegisteredProgram> {
+        self.programs
+            .iter()
+            .filter(|p| p.name == name)
+            .max_by_key(|p| p.version)
+    }
+}
+
+fn unknown(name: &str) -> Box<dyn std::error::Error> {
+    format!("{name} is neither a program hash nor a program in the registry").into()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(name: &str, hash: char) -> ProgramMetadata {
+        ProgramMetadata {
+            name: name.to_string(),
+            hash: hash.to_string().repeat(64),
+            image_file_name: "prover.img".to_string(),
+            image_file_url: "http://localhost/prover.img".to_string(),
+            image_file_checksum: "0".repeat(64),
+            resource_requirements: None,
+        }
+    }
+
+    #[test]
+    fn versions_follow_the_hashes_of_a_name() {
+        let mut registry = Registry::default();
+        assert_eq!(
+            registry.register(&program("#prover", 'a'), "tx1").version,
+            1
+        );
+        assert_eq!(
+            registry.register(&program("#prover", 'b'), "tx2").version,
+            2
+        );
+        // Deploying a registered hash again keeps its version.
+        assert_eq!(
+            registry.register(&program("#prover", 'a'), "tx3").version,
+            1
+        );
+        assert_eq!(
+            registry.register(&program("#verifier", 'c'), "tx4").version,
+            1
+        );
+
+        assert_eq!(registry.resolve("#prover").unwrap(), "a".repeat(64));
+        assert_eq!(registry.resolve("#prover@latest").unwrap(), "b".repeat(64));
+        assert_eq!(registry.resolve("#prover@2").unwrap(), "b".repeat(64));
+        assert!(registry.resolve("#prover@3").is_err());
+        assert!(registry.resolve("#prover@two").is_err());
+        assert!(registry.resolve("#unknown").is_err());
+
+        let hash = "f".repeat(64);
+        assert_eq!(registry.resolve(&hash).unwrap(), hash);
+    }
+
+    #[test]
+    fn promoting_changes_what_the_name_resolves_to() {
+        let mut registry = Registry::default();
+        registry.register(&program("#prover", 'a'), "tx1");
+        registry.register(&program("#prover", 'b'), "tx2");
+        assert_eq!(registry.promote("#prover@latest").unwrap().version, 2);
+        assert_eq!(registry.resolve("#prover").unwrap(), "b".repeat(64));
+
+        let promoted: Vec<_> = registry
+            .list(Some("#prover"))
+            .into_iter()
+            .map(|p| (p.reference(), registry.is_promoted(p)))
+            .collect();
+        assert_eq!(
+            promoted,
+            [
+                ("#prover@1".to_string(), false),
+                ("#prover@2".to_string(), true)
+            ]
+        );
+    }
+
+    #[test]
+    fn tasks_get_the_hashes_of_their_programs() {
+        let mut registry = Registry::default();
+        registry.register(&program("#prover", 'a'), "tx1");
+        let tasks = r##"[{"program":"#prover","cmd_args":[],"inputs":[{"Output":{"source_program":"#prover@1","file_name":"/workspace/proof.bin"}}]}]"##;
+        let resolved: serde_json::Value =
+            serde_json::from_str(&registry.resolve_tasks(tasks).unwrap()).unwrap();
+        let hash = "a".repeat(64);
+        assert_eq!(resolved[0]["program"], hash.as_str());
+        assert_eq!(
+            resolved[0]["inputs"][0]["Output"]["source_program"],
+            hash.as_str()
+        );
+        assert_eq!(
+            resolved[0]["inputs"][0]["Output"]["file_name"],
+            "/workspace/proof.bin"
+        );
+    }
+}
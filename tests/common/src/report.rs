@@ -0,0 +1,219 @@
+//! The `report.json` a prover returns next to its proof.
+//!
+//! It carries what `test.log` used to hold as free text: the parsed
+//! arguments, the inputs, how long each phase took, the peak memory and the
+//! final outcome.
+
+use crate::outcome::{split_existing, Outcome, Verdict};
+use crate::task::{workspace_dir, TaskOutput};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputFile {
+    pub path: String,
+    pub size: u64,
+    pub blake3: String,
+}
+
+impl InputFile {
+    pub fn read(path: &str) -> std::io::Result<Self> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update_reader(fs::File::open(path)?)?;
+        Ok(InputFile {
+            path: path.to_string(),
+            size: fs::metadata(path)?.len(),
+            blake3: hasher.finalize().to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Phase {
+    pub name: String,
+    pub seconds: f64,
+}
+
+/// Phase timings recorded from code that only gets a shared reference, such
+/// as the `Fn` callbacks of the continuation pipeline.
+#[derive(Debug, Default)]
+pub struct Timings(Mutex<Vec<Phase>>);
+
+impl Timings {
+    pub fn record(&self, name: &str, duration: Duration) {
+        log::debug!("{} took: {:?}", name, duration);
+        self.0.lock().unwrap().push(Phase {
+            name: name.to_string(),
+            seconds: duration.as_secs_f64(),
+        });
+    }
+
+    /// Runs `f` and records how long it took under `name`.
+    pub fn time<T>(&self, name: &str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let out = f();
+        self.record(name, start.elapsed());
+        out
+    }
+
+    pub fn into_phases(self) -> Vec<Phase> {
+        self.0.into_inner().unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Report {
+    pub args: serde_json::Value,
+    pub inputs: Vec<InputFile>,
+    /// Inputs that could not be read, with the reason.
+    pub missing_inputs: Vec<(String, String)>,
+    pub phases: Vec<Phase>,
+    /// The peak resident memory of the prover in KiB, when the VM exposes it.
+    pub peak_memory_kib: Option<u64>,
+    pub outcome: Option<Outcome>,
+}
+
+impl Report {
+    pub fn new<A: Serialize>(args: &A) -> Self {
+        Report {
+            args: serde_json::to_value(args).unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    pub fn add_input(&mut self, path: &str) {
+        match InputFile::read(path) {
+            Ok(input) => self.inputs.push(input),
+            Err(e) => self.missing_inputs.push((path.to_string(), e.to_string())),
+        }
+    }
+
+    /// Records the outcome and the peak memory, then writes the report to `path`.
+    pub fn finish(&mut self, outcome: &Outcome, path: &str) -> std::io::Result<()> {
+        self.outcome = Some(outcome.clone());
+        self.peak_memory_kib = peak_memory_kib();
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+}
+
+/// Returns a failed result carrying `outcome`, together with whichever of
+/// `outputs` exist, the log and the report, so the client can tell the
+/// failure apart without downloading `test.log`.
+pub fn task_failed(
+    report: &mut Report,
+    outcome: Outcome,
+    outputs: &[String],
+) -> std::result::Result<TaskOutput, Box<dyn std::error::Error>> {
+    task_failed_in(&workspace_dir(), report, outcome, outputs)
+}
+
+/// [`task_failed`] for the task whose workspace is `workspace`.
+fn task_failed_in(
+    workspace: &str,
+    report: &mut Report,
+    outcome: Outcome,
+    outputs: &[String],
+) -> std::result::Result<TaskOutput, Box<dyn std::error::Error>> {
+    report.finish(&outcome, &format!("{workspace}/report.json"))?;
+    Ok(TaskOutput::new(
+        outcome.to_bytes(),
+        returned_files(workspace, outputs),
+    ))
+}
+
+/// Returns the verdict of a verifier with the same files as [`task_failed`],
+/// whether the proof was verified or not.
+pub fn task_verdict(verdict: &Verdict, outputs: &[String]) -> TaskOutput {
+    TaskOutput::new(
+        verdict.to_bytes(),
+        returned_files(&workspace_dir(), outputs),
+    )
+}
+
+/// Whichever of `outputs`, the log and the report in `workspace` exist.
+fn returned_files(workspace: &str, outputs: &[String]) -> Vec<String> {
+    let mut files = outputs.to_vec();
+    files.push(format!("{workspace}/test.log"));
+    files.push(format!("{workspace}/report.json"));
+    split_existing(&files).0
+}
+
+/// Reads `VmHWM` from `/proc/self/status`.
+pub fn peak_memory_kib() -> Option<u64> {
+    status_field_kib(Path::new("/proc/self/status"), "VmHWM:")
+}
+
+pub(crate) fn status_field_kib(path: &Path, field: &str) -> Option<u64> {
+    fs::read_to_string(path)
+        .ok()?
+        .lines()
+        .find_map(|l| l.strip_prefix(field))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outcome::Status;
+
+    #[test]
+    fn status_fields_are_read_in_kib() {
+        let dir = std::env::temp_dir().join(format!("report-status-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let status = dir.join("status");
+        fs::write(
+            &status,
+            "Name:\tprover\nVmPeak:\t 2048 kB\nVmHWM:\t   1536 kB\nVmRSS:\t1024 kB\n",
+        )
+        .unwrap();
+        assert_eq!(status_field_kib(&status, "VmHWM:"), Some(1536));
+        assert_eq!(status_field_kib(&status, "VmRSS:"), Some(1024));
+        assert_eq!(status_field_kib(&status, "VmSwap:"), None);
+        assert_eq!(status_field_kib(&dir.join("missing"), "VmHWM:"), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn timings_keep_the_phases_in_order() {
+        let timings = Timings::default();
+        timings.record("witness", Duration::from_millis(1500));
+        assert_eq!(timings.time("proof", || 42), 42);
+        let phases = timings.into_phases();
+        let names: Vec<&str> = phases.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["witness", "proof"]);
+        assert_eq!(phases[0].seconds, 1.5);
+    }
+
+    #[test]
+    fn failed_tasks_return_only_the_existing_outputs() {
+        let dir = std::env::temp_dir().join(format!("report-failed-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let workspace = dir.to_str().unwrap();
+        let file = |name: &str| format!("{workspace}/{name}");
+        let proof = file("proof.json");
+        fs::write(&proof, "{}").unwrap();
+        fs::write(file("test.log"), "").unwrap();
+
+        let mut report = Report::new(&["--proof_file", "proof.json"]);
+        report.add_input(&proof);
+        report.add_input(&file("pil.json"));
+        let outcome = Outcome::failure(Status::ProverFailed, "boom");
+        let outputs = [proof.clone(), file("stark_verfier.circom")];
+        let output = task_failed_in(workspace, &mut report, outcome.clone(), &outputs).unwrap();
+
+        assert_eq!(output.files, [proof, file("test.log"), file("report.json")]);
+        assert_eq!(Outcome::from_bytes(&output.data).unwrap(), outcome);
+        assert_eq!(report.inputs.len(), 1);
+        assert_eq!(report.missing_inputs.len(), 1);
+        let written: Report =
+            serde_json::from_slice(&fs::read(file("report.json")).unwrap()).unwrap();
+        assert_eq!(written.outcome, Some(outcome));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
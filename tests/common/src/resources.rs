@@ -0,0 +1,183 @@
+//! Recommended prover resources, estimated from the size of the circuit.
+//!
+//! The stark prover keeps the committed, quotient, intermediate and constant
+//! polynomials evaluated on the extended domain (`2^nBitsExt` rows), the
+//! committed and constant ones on the base domain (`2^nBits` rows), and a
+//! Merkle tree over each extended stage. The estimate is that footprint,
+//! doubled for the FRI layers and working buffers, plus a fixed base.
+
+use crate::report::status_field_kib;
+use crate::tx::ResourceRequest;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Bytes of a Goldilocks field element.
+const ELEMENT_BYTES: u64 = 8;
+/// Bytes of a Merkle node: four field elements for GL, one 32-byte element for BN128.
+const NODE_BYTES: u64 = 32;
+/// The Merkle trees built over the extended domain: stages 1 to 3 and the constants.
+const TREES: u64 = 4;
+const BASE_MEM_MB: u64 = 512;
+/// Extended rows one CPU is recommended for.
+const ROWS_PER_CPU: u64 = 1 << 16;
+const MAX_CPUS: u64 = 64;
+
+/// The sizes read from the PIL json.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PilSize {
+    pub n_commitments: u64,
+    #[serde(default, rename = "nQ")]
+    pub n_q: u64,
+    #[serde(default)]
+    pub n_im: u64,
+    pub n_constants: u64,
+}
+
+/// The domain sizes read from the `StarkStruct`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainSize {
+    pub n_bits: u32,
+    pub n_bits_ext: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitSize {
+    pub pil: PilSize,
+    pub domain: DomainSize,
+}
+
+impl CircuitSize {
+    pub fn load(pil_json: &Path, stark_struct: &Path) -> Result<Self> {
+        let pil = serde_json::from_slice(&fs::read(pil_json)?)
+            .map_err(|e| format!("parsing {}: {e}", pil_json.display()))?;
+        let domain = serde_json::from_slice(&fs::read(stark_struct)?)
+            .map_err(|e| format!("parsing {}: {e}", stark_struct.display()))?;
+        Ok(CircuitSize { pil, domain })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Estimate {
+    /// Memory in MB, as in a `ResourceRequest`.
+    pub mem: u64,
+    pub cpus: u64,
+}
+
+/// Fails when the domain or the footprint doesn't fit in 64 bits, as for a
+/// corrupt or hostile `StarkStruct`.
+pub fn estimate(size: &CircuitSize) -> Result<Estimate> {
+    let pil = size.pil;
+    let domain = size.domain;
+    let rows = 1u64
+        .checked_shl(domain.n_bits)
+        .ok_or_else(|| format!("nBits {} is too large", domain.n_bits))?;
+    let ext_rows = 1u64
+        .checked_shl(domain.n_bits_ext)
+        .ok_or_else(|| format!("nBitsExt {} is too large", domain.n_bits_ext))?;
+
+    let bytes = footprint(&pil, rows, ext_rows).ok_or_else(|| {
+        format!(
+            "the memory of a circuit with nBits {} and nBitsExt {} overflows",
+            domain.n_bits, domain.n_bits_ext
+        )
+    })?;
+    Ok(Estimate {
+        mem: BASE_MEM_MB + bytes.div_ceil(1 << 20),
+        cpus: ext_rows.div_ceil(ROWS_PER_CPU).clamp(1, MAX_CPUS),
+    })
+}
+
+/// The prover memory in bytes, `None` on overflow.
+fn footprint(pil: &PilSize, rows: u64, ext_rows: u64) -> Option<u64> {
+    let base = (pil.n_commitments.checked_add(pil.n_constants)?)
+        .checked_mul(rows)?
+        .checked_mul(ELEMENT_BYTES)?;
+    let extended = [pil.n_q, pil.n_im, pil.n_constants]
+        .into_iter()
+        .try_fold(pil.n_commitments, u64::checked_add)?
+        .checked_mul(ext_rows)?
+        .checked_mul(ELEMENT_BYTES)?;
+    let trees = (TREES * 2 * NODE_BYTES).checked_mul(ext_rows)?;
+    base.checked_add(extended)?
+        .checked_add(trees)?
+        .checked_mul(2)
+}
+
+/// Warnings for each requested resource below the estimate.
+pub fn check(requested: &ResourceRequest, estimate: &Estimate) -> Vec<String> {
+    let mut warnings = vec![];
+    if requested.mem < estimate.mem {
+        warnings.push(format!(
+            "the requested memory of {} MB is below the estimated {} MB, the prover may exit abnormally",
+            requested.mem, estimate.mem
+        ));
+    }
+    if requested.cpus < estimate.cpus {
+        warnings.push(format!(
+            "the requested {} CPUs are below the recommended {}",
+            requested.cpus, estimate.cpus
+        ));
+    }
+    warnings
+}
+
+/// The memory available to new allocations in MB, from `/proc/meminfo`.
+pub fn available_memory_mb() -> Option<u64> {
+    let meminfo = Path::new("/proc/meminfo");
+    status_field_kib(meminfo, "MemAvailable:")
+        .or_else(|| status_field_kib(meminfo, "MemFree:"))
+        .map(|kib| kib / 1024)
+}
+
+/// Fails with the required and available memory when the estimate for
+/// `size` exceeds what is available. Passes when the available memory is
+/// unknown.
+pub fn check_available_memory(size: &CircuitSize) -> std::result::Result<(), String> {
+    let required = estimate(size)
+        .map_err(|e| format!("the prover memory can't be estimated: {e}"))?
+        .mem;
+    match available_memory_mb() {
+        Some(available) if available < required => Err(format!(
+            "the prover needs about {required} MB of memory, but only {available} MB is available"
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn size(n_bits: u32, n_bits_ext: u32) -> CircuitSize {
+        CircuitSize {
+            pil: PilSize {
+                n_commitments: 10,
+                n_q: 2,
+                n_im: 3,
+                n_constants: 5,
+            },
+            domain: DomainSize { n_bits, n_bits_ext },
+        }
+    }
+
+    #[test]
+    fn estimates_a_small_circuit() {
+        let estimate = estimate(&size(10, 11)).unwrap();
+        // 2 * (15 * 2^10 * 8 + 20 * 2^11 * 8 + 4 * 2 * 2^11 * 32) bytes
+        assert_eq!(estimate.mem, BASE_MEM_MB + 2);
+        assert_eq!(estimate.cpus, 1);
+    }
+
+    #[test]
+    fn rejects_domains_that_overflow() {
+        assert!(estimate(&size(64, 65)).is_err());
+        assert!(estimate(&size(20, 200)).is_err());
+        assert!(estimate(&size(40, 60)).is_err());
+        assert!(check_available_memory(&size(20, 64)).is_err());
+    }
+}
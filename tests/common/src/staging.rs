@@ -0,0 +1,110 @@
+//! Publishes local input files and returns the `Input` entries of a workflow
+//! step referencing them, so checksums and URLs are never written by hand.
+//!
+//! A file is published either by copying it into the directory an http file
+//! server serves, under `<blake3>/<file name>`, or by adding it to the
+//! client's [`FileServer`](crate::file_server::FileServer).
+
+use crate::file_server::percent_encode;
+use crate::harness::file_checksum;
+use crate::task::WORKSPACE_PATH;
+use crate::tx::ProgramData;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Copies `file` into `dir`, served as `base_url`, and returns its URL and
+/// blake3. Files are stored content-addressed as `<blake3>/<file name>`, as
+/// the [`FileServer`](crate::file_server::FileServer) serves them, so files
+/// of the same name never replace each other.
+pub fn publish_to_dir(file: &Path, dir: &Path, base_url: &str) -> Result<(String, String)> {
+    let checksum = file_checksum(file)?;
+    let name = file
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("{} has no file name", file.display()))?;
+    let target_dir = dir.join(&checksum);
+    let target = target_dir.join(name);
+    if !target.exists() {
+        fs::create_dir_all(&target_dir)?;
+        let tmp = target_dir.join(format!(".{name}.part"));
+        fs::copy(file, &tmp)?;
+        fs::rename(&tmp, &target)?;
+    }
+    let url = format!(
+        "{}/{checksum}/{}",
+        base_url.trim_end_matches('/'),
+        percent_encode(name)
+    );
+    Ok((url, checksum))
+}
+
+/// Publishes each `(vm path, file)` with `publish` and returns its `Input`
+/// entry. All the problems found are reported in one error.
+pub fn stage(
+    files: &[(String, PathBuf)],
+    mut publish: impl FnMut(&Path) -> Result<(String, String)>,
+) -> Result<Vec<ProgramData>> {
+    let mut errors = vec![];
+    let mut vm_paths = HashSet::new();
+    let mut inputs = vec![];
+    for (vm_path, file) in files {
+        if !vm_path.starts_with(&format!("{WORKSPACE_PATH}/")) {
+            errors.push(format!("{vm_path} is not under {WORKSPACE_PATH}"));
+        }
+        if !vm_paths.insert(vm_path.as_str()) {
+            errors.push(format!("{vm_path} is given twice"));
+        }
+        match publish(file) {
+            Ok((file_url, checksum)) => inputs.push(ProgramData::Input {
+                file_name: vm_path.clone(),
+                file_url,
+                checksum,
+            }),
+            Err(e) => errors.push(format!("publishing {}: {e}", file.display())),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(format!("cannot stage the inputs: {}", errors.join("; ")).into());
+    }
+    Ok(inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_of_the_same_name_do_not_collide() {
+        let root = std::env::temp_dir().join(format!("staging-{}", std::process::id()));
+        let (a, b, served) = (root.join("a"), root.join("b"), root.join("http"));
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        fs::write(a.join("data.bin"), b"x").unwrap();
+        fs::write(b.join("data.bin"), b"y").unwrap();
+
+        let files = [
+            ("/workspace/a/data.bin".to_string(), a.join("data.bin")),
+            ("/workspace/b/data.bin".to_string(), b.join("data.bin")),
+        ];
+        let inputs = stage(&files, |file| {
+            publish_to_dir(file, &served, "http://files/")
+        })
+        .unwrap();
+
+        for (input, (_, file)) in inputs.iter().zip(&files) {
+            let ProgramData::Input {
+                file_url, checksum, ..
+            } = input
+            else {
+                panic!("not an input: {input:?}");
+            };
+            assert_eq!(file_url, &format!("http://files/{checksum}/data.bin"));
+            let published = served.join(checksum).join("data.bin");
+            assert_eq!(fs::read(published).unwrap(), fs::read(file).unwrap());
+        }
+        fs::remove_dir_all(root).unwrap();
+    }
+}
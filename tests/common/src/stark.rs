@@ -0,0 +1,180 @@
+//! The `StarkStruct` parameters of a proof, as the JSON starky reads.
+//!
+//! The chunk proofs of the e2e prover are made by powdr's eStark backend,
+//! which takes no `StarkStruct` and derives one from the trace degree: a
+//! blowup of 2, two queries, GL hashing and FRI folding by 4 bits per step.
+//! [`StarkParams::estark`] is that derivation, so the circom verifier and the
+//! verifier of a chunk use the parameters its proof was made with.
+//!
+//! The security level of the parameters is estimated over the cubic
+//! extension of Goldilocks starky draws its challenges from:
+//!
+//! - conjectured: `nQueries * (nBitsExt - nBits)` bits from the queries, as
+//!   in the ethSTARK conjecture;
+//! - proven: `nQueries * log2(2 / (1 + rho))` bits, with `rho` the code
+//!   rate, the bound FRI is proven sound for in the unique-decoding regime.
+//!
+//! Both are capped by the size of the extension field over the evaluation
+//! domain, and by the collision resistance of the Merkle hash.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The FRI queries of powdr's eStark backend.
+pub const ESTARK_QUERIES: usize = 2;
+/// log2 of the blowup factor of powdr's eStark backend.
+pub const ESTARK_BLOWUP_BITS: usize = 1;
+/// The bits folded per FRI step by powdr's eStark backend.
+pub const ESTARK_FRI_STRIDE: usize = 4;
+
+/// The bits of the extension field the challenges are drawn from, `GF(p^3)`.
+pub const EXTENSION_FIELD_BITS: u32 = 3 * 64;
+/// The largest evaluation domain of Goldilocks, `2^32`.
+pub const TWO_ADICITY: usize = 32;
+
+/// The bits of security of a set of parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityLevel {
+    pub conjectured: u32,
+    pub proven: u32,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StarkStep {
+    pub nBits: usize,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StarkParams {
+    pub nBits: usize,
+    pub nBitsExt: usize,
+    pub nQueries: usize,
+    pub verificationHashType: String,
+    pub steps: Vec<StarkStep>,
+}
+
+impl StarkParams {
+    /// The parameters for a trace of `2^n_bits` rows.
+    pub fn derive(n_bits: usize, blowup_bits: usize, n_queries: usize, fri_stride: usize) -> Self {
+        let n_bits_ext = n_bits + blowup_bits;
+        StarkParams {
+            nBits: n_bits,
+            nBitsExt: n_bits_ext,
+            nQueries: n_queries,
+            verificationHashType: "GL".to_string(),
+            steps: (2..=n_bits_ext)
+                .rev()
+                .step_by(fri_stride.max(1))
+                .map(|b| StarkStep { nBits: b })
+                .collect(),
+        }
+    }
+
+    /// The parameters powdr's eStark backend proves a trace of `2^n_bits`
+    /// rows with.
+    pub fn estark(n_bits: usize) -> Self {
+        Self::derive(
+            n_bits,
+            ESTARK_BLOWUP_BITS,
+            ESTARK_QUERIES,
+            ESTARK_FRI_STRIDE,
+        )
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)
+            .map_err(|e| format!("parsing {}: {e}", path.display()))?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The bits of security of the parameters; see the module documentation.
+    pub fn security(&self) -> SecurityLevel {
+        let blowup_bits = self.nBitsExt.saturating_sub(self.nBits);
+        let rate = 0.5f64.powi(blowup_bits as i32);
+        let proven_per_query = (2.0 / (1.0 + rate)).log2();
+        let cap = EXTENSION_FIELD_BITS
+            .saturating_sub(self.nBitsExt as u32)
+            .min(hash_bits(&self.verificationHashType));
+        SecurityLevel {
+            conjectured: ((self.nQueries * blowup_bits) as u32).min(cap),
+            proven: ((self.nQueries as f64 * proven_per_query).floor() as u32).min(cap),
+        }
+    }
+
+    /// Fails when the conjectured security is below `min_bits`.
+    pub fn check_security(&self, min_bits: u32) -> Result<SecurityLevel> {
+        let level = self.security();
+        if level.conjectured < min_bits {
+            return Err(format!(
+                "the StarkStruct gives {} bits of conjectured security ({} proven), \
+                 below the minimum of {min_bits}",
+                level.conjectured, level.proven
+            )
+            .into());
+        }
+        Ok(level)
+    }
+}
+
+/// The collision resistance of the Merkle hash: Poseidon over four
+/// Goldilocks elements for GL, over one BN254 element for BN128.
+fn hash_bits(verification_hash_type: &str) -> u32 {
+    match verification_hash_type {
+        "BN128" => 127,
+        _ => 128,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estark_parameters_follow_the_trace_degree() {
+        let params = StarkParams::estark(20);
+        assert_eq!(params.nBitsExt, 21);
+        assert_eq!(params.nQueries, 2);
+        assert_eq!(params.verificationHashType, "GL");
+        let steps: Vec<usize> = params.steps.iter().map(|s| s.nBits).collect();
+        assert_eq!(steps, [21, 17, 13, 9, 5]);
+    }
+
+    #[test]
+    fn estark_parameters_give_two_bits_at_most() {
+        let params = StarkParams::estark(TWO_ADICITY - ESTARK_BLOWUP_BITS);
+        assert_eq!(
+            params.security(),
+            SecurityLevel {
+                conjectured: 2,
+                proven: 0
+            }
+        );
+        assert!(params.check_security(2).is_ok());
+        assert!(params.check_security(80).is_err());
+    }
+
+    #[test]
+    fn estark_parameters_reject_a_minimum_above_two_bits() {
+        for n_bits in [4, 16, 20, TWO_ADICITY - ESTARK_BLOWUP_BITS] {
+            let params = StarkParams::estark(n_bits);
+            assert_eq!(params.check_security(2).unwrap().conjectured, 2);
+            assert!(params.check_security(3).is_err(), "2^{n_bits} rows");
+        }
+    }
+
+    #[test]
+    fn security_grows_with_the_queries_and_the_blowup() {
+        let params = StarkParams::derive(20, 3, 40, 4);
+        assert_eq!(params.security().conjectured, 120);
+        assert!(params.check_security(100).is_ok());
+    }
+}
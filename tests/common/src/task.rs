@@ -0,0 +1,67 @@
+//! What a program returns for a task, and how it runs outside Gevulot.
+//!
+//! The programs compute a [`TaskOutput`] from the task arguments. Under
+//! Gevulot it becomes the `TaskResult` of the shim; under the local harness
+//! (see [`crate::harness`]) the task is read from and the output written to
+//! the files named by [`TASK_ENV`] and [`RESULT_ENV`], and the workspace is
+//! the directory named by [`WORKSPACE_ENV`] instead of `/workspace`.
+
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+
+/// The mount point of the task files in the VM.
+pub const WORKSPACE_PATH: &str = "/workspace";
+
+pub const TASK_ENV: &str = "EIGEN_GEVULOT_LOCAL_TASK";
+pub const RESULT_ENV: &str = "EIGEN_GEVULOT_LOCAL_RESULT";
+pub const WORKSPACE_ENV: &str = "EIGEN_GEVULOT_WORKSPACE";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalTask {
+    pub id: String,
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskOutput {
+    pub data: Vec<u8>,
+    pub files: Vec<String>,
+}
+
+impl TaskOutput {
+    pub fn new(data: Vec<u8>, files: Vec<String>) -> Self {
+        TaskOutput { data, files }
+    }
+}
+
+/// The workspace of the running task, `/workspace` unless run locally.
+pub fn workspace_dir() -> String {
+    std::env::var(WORKSPACE_ENV).unwrap_or_else(|_| WORKSPACE_PATH.to_string())
+}
+
+/// The path of `name` inside the workspace of the running task.
+pub fn workspace_file(name: &str) -> String {
+    format!("{}/{}", workspace_dir(), name)
+}
+
+/// Whether the program was started by the local harness.
+pub fn is_local() -> bool {
+    std::env::var_os(TASK_ENV).is_some()
+}
+
+/// Runs the task described by [`TASK_ENV`] with `f` and writes its output
+/// to [`RESULT_ENV`].
+pub fn run_local<F>(f: F) -> Result<(), Box<dyn Error>>
+where
+    F: FnOnce(&[String]) -> Result<TaskOutput, Box<dyn Error>>,
+{
+    let task_file = std::env::var(TASK_ENV)?;
+    let result_file = std::env::var(RESULT_ENV)?;
+    let task: LocalTask = serde_json::from_slice(&fs::read(task_file)?)?;
+    log::info!("running local task {}", task.id);
+
+    let output = f(&task.args)?;
+    fs::write(result_file, serde_json::to_vec(&output)?)?;
+    Ok(())
+}
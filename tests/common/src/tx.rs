@@ -0,0 +1,169 @@
+//! The transactions and transaction trees of a node's JSON-RPC API, as JSON.
+//!
+//! These mirror the `TransactionOutput` and `TransactionTree` of
+//! gevulot-node (see the `get-tx` output in the README) without depending
+//! on it, so the mock node and the client tooling share one view.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxFile {
+    pub url: String,
+    pub checksum: String,
+    pub vm_path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceRequest {
+    pub cpus: u64,
+    pub mem: u64,
+    pub gpus: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramMetadata {
+    pub name: String,
+    pub hash: String,
+    pub image_file_name: String,
+    pub image_file_url: String,
+    pub image_file_checksum: String,
+    pub resource_requirements: Option<ResourceRequest>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProgramData {
+    Input {
+        file_name: String,
+        file_url: String,
+        checksum: String,
+    },
+    Output {
+        source_program: String,
+        file_name: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkflowStep {
+    pub program: String,
+    pub args: Vec<String>,
+    pub inputs: Vec<ProgramData>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workflow {
+    pub steps: Vec<WorkflowStep>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Payload {
+    Empty,
+    Deploy {
+        name: String,
+        prover: Box<ProgramMetadata>,
+        verifier: Box<ProgramMetadata>,
+    },
+    Run {
+        workflow: Workflow,
+    },
+    Proof {
+        parent: String,
+        prover: String,
+        #[serde(with = "base64_bytes")]
+        proof: Vec<u8>,
+        files: Vec<TxFile>,
+    },
+    ProofKey {
+        parent: String,
+        #[serde(with = "base64_bytes")]
+        key: Vec<u8>,
+    },
+    Verification {
+        parent: String,
+        verifier: String,
+        #[serde(with = "base64_bytes")]
+        verification: Vec<u8>,
+        files: Vec<TxFile>,
+    },
+    Cancel {
+        parent: String,
+    },
+}
+
+impl Payload {
+    /// The name of the variant, as shown by `print-tx-tree`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Payload::Empty => "Empty",
+            Payload::Deploy { .. } => "Deploy",
+            Payload::Run { .. } => "Run",
+            Payload::Proof { .. } => "Proof",
+            Payload::ProofKey { .. } => "ProofKey",
+            Payload::Verification { .. } => "Verification",
+            Payload::Cancel { .. } => "Cancel",
+        }
+    }
+
+    /// The files a Proof or Verification returned.
+    pub fn files(&self) -> &[TxFile] {
+        match self {
+            Payload::Proof { files, .. } | Payload::Verification { files, .. } => files,
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxOutput {
+    pub author: String,
+    pub hash: String,
+    pub payload: Payload,
+    pub nonce: u64,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TxTree {
+    Root { children: Vec<TxTree>, hash: String },
+    Node { children: Vec<TxTree>, hash: String },
+    Leaf { hash: String },
+}
+
+impl TxTree {
+    pub fn hash(&self) -> &str {
+        match self {
+            TxTree::Root { hash, .. } | TxTree::Node { hash, .. } | TxTree::Leaf { hash } => hash,
+        }
+    }
+
+    pub fn children(&self) -> &[TxTree] {
+        match self {
+            TxTree::Root { children, .. } | TxTree::Node { children, .. } => children,
+            TxTree::Leaf { .. } => &[],
+        }
+    }
+
+    /// The hashes of this node and all its descendants, depth first.
+    pub fn hashes(&self) -> Vec<&str> {
+        let mut out = vec![self.hash()];
+        for child in self.children() {
+            out.extend(child.hashes());
+        }
+        out
+    }
+}
+
+/// Bytes as the base64 string the node uses in its JSON output.
+pub mod base64_bytes {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        STANDARD.decode(s).map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,511 @@
+//! Builds the workflow of a Run transaction from typed steps.
+//!
+//! A workflow is a prover step followed by a verifier step. Inputs are local
+//! files, published under the file server URL, or files already reachable by
+//! URL; the verifier also takes the files the prover returned. [`Job`] is the
+//! same description as a JSON file:
+//!
+//! ```json
+//! {
+//!   "file_server": "http://4.145.88.10:8080",
+//!   "prover": {
+//!     "program": "<prover hash>",
+//!     "args": [
+//!       {"name": "--piljson", "value": "/workspace/jsn_fibonacci.recursive2.pil.json"},
+//!       {"name": "--norm_stage", "value": ""}
+//!     ],
+//!     "inputs": [{"file": "input-files/jsn_fibonacci.recursive2.pil.json"}]
+//!   },
+//!   "verifier": {
+//!     "program": "<verifier hash>",
+//!     "args": [{"name": "--in_file", "value": "/workspace/proof.json"}],
+//!     "forward": ["/workspace/proof.json"]
+//!   }
+//! }
+//! ```
+//!
+//! The arguments are a list, as in `cmd_args`, so they keep their order and
+//! the same flag can be given more than once.
+
+use crate::harness::{file_checksum, CmdArg};
+use crate::task::WORKSPACE_PATH;
+use crate::tx::{ProgramData, Workflow, WorkflowStep};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepInput {
+    /// A local file, published as `<file server>/<file name>`.
+    File { path: PathBuf, vm_path: String },
+    /// A file the node downloads from `url`.
+    Url {
+        url: String,
+        checksum: String,
+        vm_path: String,
+    },
+    /// A file returned by the step running `source_program`.
+    Output {
+        source_program: String,
+        file_name: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepBuilder {
+    program: String,
+    args: Vec<(String, String)>,
+    inputs: Vec<StepInput>,
+}
+
+impl StepBuilder {
+    pub fn new(program: impl Into<String>) -> Self {
+        StepBuilder {
+            program: program.into(),
+            args: vec![],
+            inputs: vec![],
+        }
+    }
+
+    pub fn program(&self) -> &str {
+        &self.program
+    }
+
+    pub fn arg(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.args.push((name.into(), value.into()));
+        self
+    }
+
+    /// A boolean flag, passed with an empty value as Gevulot does.
+    pub fn flag(self, name: impl Into<String>) -> Self {
+        self.arg(name, "")
+    }
+
+    /// A local file, stored at `vm_path` or `/workspace/<file name>`.
+    pub fn input_file(mut self, path: impl Into<PathBuf>, vm_path: Option<String>) -> Self {
+        let path = path.into();
+        let vm_path = vm_path.unwrap_or_else(|| workspace_path(&path));
+        self.inputs.push(StepInput::File { path, vm_path });
+        self
+    }
+
+    pub fn input_url(
+        mut self,
+        url: impl Into<String>,
+        checksum: impl Into<String>,
+        vm_path: impl Into<String>,
+    ) -> Self {
+        self.inputs.push(StepInput::Url {
+            url: url.into(),
+            checksum: checksum.into(),
+            vm_path: vm_path.into(),
+        });
+        self
+    }
+
+    /// A file returned by an earlier step, at the same path.
+    pub fn output_from(
+        mut self,
+        source_program: impl Into<String>,
+        file_name: impl Into<String>,
+    ) -> Self {
+        self.inputs.push(StepInput::Output {
+            source_program: source_program.into(),
+            file_name: file_name.into(),
+        });
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WorkflowBuilder {
+    file_server: Option<String>,
+    prover: Option<StepBuilder>,
+    verifier: Option<StepBuilder>,
+}
+
+impl WorkflowBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The base URL local input files are published under.
+    pub fn file_server(mut self, url: impl Into<String>) -> Self {
+        self.file_server = Some(url.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    pub fn prover(mut self, step: StepBuilder) -> Self {
+        self.prover = Some(step);
+        self
+    }
+
+    pub fn verifier(mut self, step: StepBuilder) -> Self {
+        self.verifier = Some(step);
+        self
+    }
+
+    /// Replaces every local file with the URL and checksum `publish` returns
+    /// for it, eg. once added to the client's file server.
+    pub fn publish_files(
+        mut self,
+        mut publish: impl FnMut(&Path) -> Result<(String, String)>,
+    ) -> Result<Self> {
+        for step in [&mut self.prover, &mut self.verifier].into_iter().flatten() {
+            for input in &mut step.inputs {
+                if let StepInput::File { path, vm_path } = input {
+                    let (url, checksum) =
+                        publish(path).map_err(|e| format!("publishing {}: {e}", path.display()))?;
+                    *input = StepInput::Url {
+                        url,
+                        checksum,
+                        vm_path: vm_path.clone(),
+                    };
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Checks the steps, checksums the local files and returns the workflow.
+    /// All the problems found are reported in one error.
+    pub fn build(&self) -> Result<Workflow> {
+        let mut errors = vec![];
+        let mut steps = vec![];
+        let mut programs = HashSet::new();
+        let named = [("prover", &self.prover), ("verifier", &self.verifier)];
+        for (name, step) in named {
+            match step {
+                Some(step) => {
+                    if let Some(step) = self.build_step(name, step, &programs, &mut errors) {
+                        steps.push(step);
+                    }
+                    programs.insert(step.program.as_str());
+                }
+                None => errors.push(format!("the workflow has no {name} step")),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(format!("invalid workflow: {}", errors.join("; ")).into());
+        }
+        Ok(Workflow { steps })
+    }
+
+    fn build_step(
+        &self,
+        name: &str,
+        step: &StepBuilder,
+        earlier: &HashSet<&str>,
+        errors: &mut Vec<String>,
+    ) -> Option<WorkflowStep> {
+        let count = errors.len();
+        if !is_hash(&step.program) {
+            errors.push(format!(
+                "the {name} program {} is not a program hash",
+                step.program
+            ));
+        }
+
+        let mut vm_paths = HashSet::new();
+        let mut inputs = vec![];
+        for input in &step.inputs {
+            let vm_path = match input {
+                StepInput::File { vm_path, .. } | StepInput::Url { vm_path, .. } => vm_path,
+                StepInput::Output { file_name, .. } => file_name,
+            };
+            if !vm_path.starts_with(&format!("{WORKSPACE_PATH}/")) {
+                errors.push(format!(
+                    "{name} input {vm_path} is not under {WORKSPACE_PATH}"
+                ));
+            }
+            if !vm_paths.insert(vm_path.as_str()) {
+                errors.push(format!("{name} input {vm_path} is given twice"));
+            }
+            match self.program_data(input) {
+                Ok(data) => inputs.push(data),
+                Err(e) => errors.push(format!("{name} input {vm_path}: {e}")),
+            }
+            if let StepInput::Output { source_program, .. } = input {
+                if !earlier.contains(source_program.as_str()) {
+                    errors.push(format!(
+                        "{name} input {vm_path} comes from {source_program}, which is not an earlier step"
+                    ));
+                }
+            }
+        }
+
+        (errors.len() == count).then(|| WorkflowStep {
+            program: step.program.clone(),
+            args: step
+                .args
+                .iter()
+                .flat_map(|(n, v)| [n.clone(), v.clone()])
+                .collect(),
+            inputs,
+        })
+    }
+
+    fn program_data(&self, input: &StepInput) -> Result<ProgramData> {
+        Ok(match input {
+            StepInput::File { path, vm_path } => {
+                let server = self
+                    .file_server
+                    .as_ref()
+                    .ok_or("local files need a file server URL")?;
+                let file_name = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .ok_or_else(|| format!("{} has no file name", path.display()))?;
+                ProgramData::Input {
+                    file_name: vm_path.clone(),
+                    file_url: format!("{server}/{file_name}"),
+                    checksum: file_checksum(path)
+                        .map_err(|e| format!("reading {}: {e}", path.display()))?,
+                }
+            }
+            StepInput::Url {
+                url,
+                checksum,
+                vm_path,
+            } => {
+                if !is_hash(checksum) {
+                    return Err(format!("{checksum} is not a blake3 checksum").into());
+                }
+                ProgramData::Input {
+                    file_name: vm_path.clone(),
+                    file_url: url.clone(),
+                    checksum: checksum.clone(),
+                }
+            }
+            StepInput::Output {
+                source_program,
+                file_name,
+            } => ProgramData::Output {
+                source_program: source_program.clone(),
+                file_name: file_name.clone(),
+            },
+        })
+    }
+}
+
+/// A 32-byte hash in hex, as program hashes and blake3 checksums are written.
+pub fn is_hash(s: &str) -> bool {
+    s.len() == 64 && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn workspace_path(path: &Path) -> String {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    format!("{WORKSPACE_PATH}/{name}")
+}
+
+/// An input in a job file: a local `file` or a `url` with its `checksum`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum JobInput {
+    File {
+        file: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        vm_path: Option<String>,
+    },
+    Url {
+        url: String,
+        checksum: String,
+        vm_path: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobStep {
+    pub program: String,
+    /// The arguments, in order.
+    #[serde(default)]
+    pub args: Vec<CmdArg>,
+    #[serde(default)]
+    pub inputs: Vec<JobInput>,
+    /// Files the previous step returned, passed on at the same path.
+    #[serde(default)]
+    pub forward: Vec<String>,
+}
+
+/// A workflow as a declarative job file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Job {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_server: Option<String>,
+    pub prover: JobStep,
+    pub verifier: JobStep,
+}
+
+impl Job {
+    /// Reads a job file. Relative input paths are taken from its directory.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut job: Job = serde_json::from_slice(&fs::read(path)?)
+            .map_err(|e| format!("parsing {}: {e}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        for step in [&mut job.prover, &mut job.verifier] {
+            for input in &mut step.inputs {
+                if let JobInput::File { file, .. } = input {
+                    *file = base.join(&*file);
+                }
+            }
+        }
+        Ok(job)
+    }
+
+    pub fn builder(&self) -> WorkflowBuilder {
+        let mut builder = WorkflowBuilder::new();
+        if let Some(url) = &self.file_server {
+            builder = builder.file_server(url);
+        }
+        builder
+            .prover(step_builder(&self.prover, None))
+            .verifier(step_builder(&self.verifier, Some(&self.prover.program)))
+    }
+}
+
+fn step_builder(step: &JobStep, previous: Option<&str>) -> StepBuilder {
+    let mut builder = StepBuilder::new(&step.program);
+    for arg in &step.args {
+        builder = builder.arg(&arg.name, &arg.value);
+    }
+    for input in &step.inputs {
+        builder = match input {
+            JobInput::File { file, vm_path } => builder.input_file(file, vm_path.clone()),
+            JobInput::Url {
+                url,
+                checksum,
+                vm_path,
+            } => builder.input_url(url, checksum, vm_path),
+        };
+    }
+    // A forward without a previous step is left to `build` to report.
+    let source = previous.unwrap_or_default();
+    for file in &step.forward {
+        builder = builder.output_from(source, file);
+    }
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn job_arguments_keep_their_order_and_repeats() {
+        let prover = "a".repeat(64);
+        let verifier = "b".repeat(64);
+        let json = format!(
+            r#"{{
+                "prover": {{
+                    "program": "{prover}",
+                    "args": [
+                        {{"name": "--agg_zkin", "value": "/workspace/chunk_1.zkin.json"}},
+                        {{"name": "--agg_zkin", "value": "/workspace/chunk_0.zkin.json"}},
+                        {{"name": "--norm_stage", "value": ""}}
+                    ]
+                }},
+                "verifier": {{"program": "{verifier}"}}
+            }}"#
+        );
+        let job: Job = serde_json::from_str(&json).unwrap();
+        let workflow = job.builder().build().unwrap();
+        assert_eq!(
+            workflow.steps[0].args,
+            [
+                "--agg_zkin",
+                "/workspace/chunk_1.zkin.json",
+                "--agg_zkin",
+                "/workspace/chunk_0.zkin.json",
+                "--norm_stage",
+                "",
+            ]
+        );
+    }
+
+    fn prover() -> StepBuilder {
+        StepBuilder::new("a".repeat(64))
+    }
+
+    fn verifier() -> StepBuilder {
+        StepBuilder::new("b".repeat(64)).output_from("a".repeat(64), "/workspace/proof.json")
+    }
+
+    /// The error of building a workflow of `prover` and `verifier`.
+    fn build_error(prover: StepBuilder, verifier: StepBuilder) -> String {
+        WorkflowBuilder::new()
+            .prover(prover)
+            .verifier(verifier)
+            .build()
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn valid_steps_build() {
+        let checksum = "c".repeat(64);
+        let workflow = WorkflowBuilder::new()
+            .prover(prover().input_url("http://host/pil.json", &checksum, "/workspace/pil.json"))
+            .verifier(verifier())
+            .build()
+            .unwrap();
+        assert_eq!(workflow.steps.len(), 2);
+    }
+
+    #[test]
+    fn programs_must_be_hashes() {
+        let err = build_error(StepBuilder::new("#eigen-gevulot-prover"), verifier());
+        assert!(
+            err.contains("the prover program #eigen-gevulot-prover is not a program hash"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn vm_paths_must_be_unique() {
+        let checksum = "c".repeat(64);
+        let prover = prover()
+            .input_url("http://host/a.json", &checksum, "/workspace/pil.json")
+            .input_url("http://host/b.json", &checksum, "/workspace/pil.json");
+        let err = build_error(prover, verifier());
+        assert!(
+            err.contains("prover input /workspace/pil.json is given twice"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn inputs_must_be_under_the_workspace() {
+        let checksum = "c".repeat(64);
+        let prover = prover().input_url("http://host/pil.json", &checksum, "/gevulot/pil.json");
+        let err = build_error(prover, verifier());
+        assert!(
+            err.contains("prover input /gevulot/pil.json is not under /workspace"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn outputs_must_come_from_an_earlier_step() {
+        let prover = prover().output_from("b".repeat(64), "/workspace/proof.json");
+        let err = build_error(prover, verifier());
+        assert!(err.contains("which is not an earlier step"), "{err}");
+    }
+
+    #[test]
+    fn local_files_need_a_file_server() {
+        let job: Job = serde_json::from_str(&format!(
+            r#"{{
+                "prover": {{"program": "{}", "inputs": [{{"file": "pil.json"}}]}},
+                "verifier": {{"program": "{}"}}
+            }}"#,
+            "a".repeat(64),
+            "b".repeat(64)
+        ))
+        .unwrap();
+        let err = job.builder().build().unwrap_err().to_string();
+        assert!(err.contains("local files need a file server URL"), "{err}");
+    }
+}
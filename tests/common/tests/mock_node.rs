@@ -0,0 +1,256 @@
+//! Sends a Run workflow to a [`MockNode`] over JSON-RPC and checks the Proof
+//! and Verification transactions it records, with no network involved.
+//!
+//! This binary is its own prover and verifier: the harness starts it in
+//! local mode with `prove` or `verify` as the task arguments.
+
+use eigen_gevulot_common::harness::{file_checksum, Harness, Step, TaskInput};
+use eigen_gevulot_common::mock_node::MockNode;
+use eigen_gevulot_common::outcome::{Outcome, Verdict};
+use eigen_gevulot_common::task::{self, workspace_file, TaskOutput};
+use eigen_gevulot_common::tx::{Payload, ProgramData, TxOutput, TxTree, Workflow, WorkflowStep};
+use serde_json::{json, Value};
+use std::fs;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const PROVER: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+const VERIFIER: &str = "2222222222222222222222222222222222222222222222222222222222222222";
+const INPUT: &str = "the witness";
+
+fn main() -> Result<()> {
+    if task::is_local() {
+        return task::run_local(fixture);
+    }
+    run_workflow_records_proof_and_verification()?;
+    println!("test run_workflow_records_proof_and_verification ... ok");
+    harness_rejects_inputs_not_staged()?;
+    println!("test harness_rejects_inputs_not_staged ... ok");
+    Ok(())
+}
+
+/// The fixture programs: `prove` returns the reversed input as the proof,
+/// `verify` accepts the proof when it is the reversed witness.
+fn fixture(args: &[String]) -> Result<TaskOutput> {
+    match args.first().map(String::as_str) {
+        Some("prove") => {
+            let input = fs::read_to_string(workspace_file("input.txt"))?;
+            let proof: String = input.chars().rev().collect();
+            fs::write(workspace_file("proof.txt"), proof)?;
+            Ok(TaskOutput::new(
+                Outcome::success().to_bytes(),
+                vec![workspace_file("proof.txt")],
+            ))
+        }
+        Some("verify") => {
+            let proof = fs::read_to_string(workspace_file("proof.txt"))?;
+            let verified = proof.chars().rev().collect::<String>() == INPUT;
+            let verdict = Verdict {
+                verified,
+                reason: format!(
+                    "the proof is {}",
+                    if verified { "valid" } else { "invalid" }
+                ),
+                publics: Vec::new(),
+            };
+            Ok(TaskOutput::new(verdict.to_bytes(), vec![]))
+        }
+        other => Err(format!("unknown fixture step {other:?}").into()),
+    }
+}
+
+fn run_workflow_records_proof_and_verification() -> Result<()> {
+    let dir = scratch_dir("mock-node")?;
+    let input = dir.join("input.txt");
+    fs::write(&input, INPUT)?;
+
+    let port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+    let url = format!("http://127.0.0.1:{port}");
+    let mut node = MockNode::new(dir.join("runs"), url.clone());
+    let exe = std::env::current_exe()?;
+    node.programs = [
+        (PROVER.to_string(), exe.clone()),
+        (VERIFIER.to_string(), exe),
+    ]
+    .into_iter()
+    .collect();
+    let server = node.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = server.serve(&format!("127.0.0.1:{port}")) {
+            eprintln!("mock node stopped: {e}");
+        }
+    });
+
+    let workflow = Workflow {
+        steps: vec![
+            WorkflowStep {
+                program: PROVER.to_string(),
+                args: vec!["prove".to_string()],
+                inputs: vec![ProgramData::Input {
+                    file_name: "/workspace/input.txt".to_string(),
+                    file_url: format!("file://{}", input.display()),
+                    checksum: file_checksum(&input)?,
+                }],
+            },
+            WorkflowStep {
+                program: VERIFIER.to_string(),
+                args: vec!["verify".to_string()],
+                inputs: vec![ProgramData::Output {
+                    source_program: PROVER.to_string(),
+                    file_name: "/workspace/proof.txt".to_string(),
+                }],
+            },
+        ],
+    };
+    let run_hash = "3333333333333333333333333333333333333333333333333333333333333333";
+    let tx = json!({
+        "author": "test",
+        "hash": run_hash,
+        "payload": Payload::Run { workflow },
+        "nonce": 0,
+        "signature": "",
+    });
+    let sent = rpc_when_up(&url, "sendTransaction", tx)?;
+    assert_eq!(sent, json!({ "Ok": null }));
+
+    let tree = wait_for_tree(&url, run_hash, Duration::from_secs(60))?;
+    let [TxTree::Node {
+        hash: proof_hash,
+        children,
+    }] = tree.children()
+    else {
+        return Err(format!("expected one Proof node, got {tree:?}").into());
+    };
+    let [TxTree::Leaf {
+        hash: verification_hash,
+    }] = &children[..]
+    else {
+        return Err(format!("expected one Verification leaf, got {children:?}").into());
+    };
+
+    let proof = get_transaction(&url, proof_hash)?;
+    let Payload::Proof {
+        parent,
+        prover,
+        proof: data,
+        files,
+    } = &proof.payload
+    else {
+        return Err(format!("expected a Proof, got {proof:?}").into());
+    };
+    assert_eq!(parent, run_hash);
+    assert_eq!(prover, PROVER);
+    assert!(Outcome::from_bytes(data)?.is_success());
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].vm_path, "/workspace/proof.txt");
+    let served = ureq::get(&files[0].url).call()?.into_string()?;
+    assert_eq!(served, "ssentiw eht");
+    assert_eq!(
+        blake3::hash(served.as_bytes()).to_string(),
+        files[0].checksum
+    );
+
+    let verification = get_transaction(&url, verification_hash)?;
+    let Payload::Verification {
+        parent,
+        verifier,
+        verification: data,
+        ..
+    } = &verification.payload
+    else {
+        return Err(format!("expected a Verification, got {verification:?}").into());
+    };
+    assert_eq!(parent, proof_hash);
+    assert_eq!(verifier, VERIFIER);
+    let verdict = Verdict::from_bytes(data)?;
+    assert!(verdict.verified, "{verdict:?}");
+    Ok(())
+}
+
+fn harness_rejects_inputs_not_staged() -> Result<()> {
+    let dir = scratch_dir("harness")?;
+    let harness = Harness {
+        programs: [(PROVER.to_string(), std::env::current_exe()?)]
+            .into_iter()
+            .collect(),
+        input_dir: Some(dir.clone()),
+        work_dir: Some(dir.join("runs")),
+        ..Default::default()
+    };
+    let step = Step {
+        program: PROVER.to_string(),
+        args: vec!["prove".to_string()],
+        inputs: vec![TaskInput::Input {
+            local_path: blake3::hash(INPUT.as_bytes()).to_string(),
+            vm_path: "/workspace/input.txt".to_string(),
+            file_url: Some("http://192.0.2.1:8080/input.txt".to_string()),
+        }],
+    };
+    let err = harness
+        .run(&[step])
+        .expect_err("the input is not staged")
+        .to_string();
+    assert!(err.contains("input not staged"), "{err}");
+    Ok(())
+}
+
+fn scratch_dir(name: &str) -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!("eigen-gevulot-{name}-{}", std::process::id()));
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+    Ok(fs::canonicalize(dir)?)
+}
+
+fn rpc(url: &str, method: &str, param: Value) -> Result<Value> {
+    let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": [param] });
+    let response = ureq::post(url)
+        .send_string(&body.to_string())?
+        .into_string()?;
+    let response: Value = serde_json::from_str(&response)?;
+    Ok(response["result"].clone())
+}
+
+/// Calls `method` once the node accepts connections.
+fn rpc_when_up(url: &str, method: &str, param: Value) -> Result<Value> {
+    let start = Instant::now();
+    loop {
+        match rpc(url, method, param.clone()) {
+            Ok(result) => return Ok(result),
+            Err(_) if start.elapsed() < Duration::from_secs(10) => {
+                std::thread::sleep(Duration::from_millis(50))
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Polls the tree of `hash` until its Proof has a Verification under it.
+fn wait_for_tree(url: &str, hash: &str, timeout: Duration) -> Result<TxTree> {
+    let start = Instant::now();
+    loop {
+        let result = rpc(url, "getTransactionTree", json!(hash))?;
+        if let Some(tree) = result.get("Ok") {
+            let tree: TxTree = serde_json::from_value(tree.clone())?;
+            if tree.children().iter().any(|c| !c.children().is_empty()) {
+                return Ok(tree);
+            }
+        }
+        if start.elapsed() > timeout {
+            return Err(format!("no Verification for {hash} after {timeout:?}: {result}").into());
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+fn get_transaction(url: &str, hash: &str) -> Result<TxOutput> {
+    let result = rpc(url, "getTransaction", json!(hash))?;
+    let tx = result
+        .get("Ok")
+        .ok_or_else(|| format!("no transaction {hash}: {result}"))?;
+    Ok(serde_json::from_value(tx.clone())?)
+}
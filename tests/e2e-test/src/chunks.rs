@@ -0,0 +1,78 @@
+//! Host-side generation of the bootloader-input files the prover consumes.
+
+use eigen_gevulot_common::chunks::{write_chunks, ChunkManifest};
+use powdr::number::{FieldElement, GoldilocksField};
+use powdr::riscv::continuations::rust_continuations_dry_run;
+use powdr::riscv::{compile_rust, Runtime};
+use powdr::Pipeline;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const TEST_CHANNEL: u32 = 1;
+
+/// Compiles the guest program in `workspace` into `output_path` and runs the
+/// continuations dry run over `suite_json`. Returns the compiled `.asm` and
+/// the bootloader input and shutdown routine start of each chunk.
+pub fn zkvm_generate_chunks(
+    workspace: &str,
+    suite_json: &String,
+    output_path: &str,
+) -> Result<(PathBuf, Vec<(Vec<GoldilocksField>, u64)>)> {
+    log::debug!("Compiling Rust...");
+    let force_overwrite = true;
+    let with_bootloader = true;
+    let (asm_file_path, asm_contents) = compile_rust::<GoldilocksField>(
+        workspace,
+        Path::new(output_path),
+        force_overwrite,
+        &Runtime::base().with_poseidon(),
+        with_bootloader,
+    )
+    .ok_or_else(|| format!("could not compile rust in {workspace}"))?;
+
+    let mut pipeline = Pipeline::<GoldilocksField>::default()
+        .with_output(output_path.into(), true)
+        .from_asm_string(asm_contents.clone(), Some(asm_file_path.clone()))
+        .with_prover_inputs(Default::default())
+        .add_data(TEST_CHANNEL, suite_json);
+
+    log::debug!("Running powdr-riscv executor in trace mode for continuations...");
+    let start = Instant::now();
+
+    let bootloader_inputs = rust_continuations_dry_run(&mut pipeline);
+
+    let duration = start.elapsed();
+    log::debug!(
+        "Trace executor took: {:?}, input size: {:?}",
+        duration,
+        bootloader_inputs.len()
+    );
+
+    Ok((asm_file_path, bootloader_inputs))
+}
+
+/// Generates the chunks of `task_name` and writes their bootloader-input
+/// files and manifest into `output_path`.
+pub fn generate_chunk_files(
+    task_name: &str,
+    workspace: &str,
+    trace_file: &Path,
+    output_path: &str,
+) -> Result<(ChunkManifest, PathBuf)> {
+    let suite_json = std::fs::read_to_string(trace_file)
+        .map_err(|e| format!("reading {}: {e}", trace_file.display()))?;
+    let (asm_file, inputs) = zkvm_generate_chunks(workspace, &suite_json, output_path)?;
+    let chunks = inputs
+        .into_iter()
+        .map(|(bi, start)| (bi.iter().map(|e| e.to_degree()).collect(), start))
+        .collect();
+    write_chunks(
+        task_name,
+        &asm_file,
+        trace_file,
+        chunks,
+        Path::new(output_path),
+    )
+}
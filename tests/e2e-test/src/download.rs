@@ -0,0 +1,35 @@
+//! Fetches the result files of a workflow from its Verification leaf.
+
+use crate::node::{get_transaction, get_tx_tree, parse_hash};
+use eigen_gevulot_common::download::download_files;
+use eigen_gevulot_common::progress::WorkflowProgress;
+use gevulot_node::{rpc_client::RpcClient, types::Hash};
+use std::path::{Path, PathBuf};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Walks the tree of `tx_hash` to its Verification leaf and downloads every
+/// file it lists under `out_dir`, checking their checksums.
+pub async fn download_results(
+    client: &RpcClient,
+    tx_hash: &Hash,
+    out_dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    let tree = get_tx_tree(client, tx_hash)
+        .await?
+        .ok_or_else(|| format!("no result for {tx_hash} yet"))?;
+
+    let mut progress = WorkflowProgress::default();
+    for hash in tree.hashes().into_iter().skip(1) {
+        progress.record(get_transaction(client, &parse_hash(hash)?).await?);
+    }
+    let verification = progress
+        .verification
+        .ok_or_else(|| format!("the verifier of {tx_hash} has not returned yet"))?;
+
+    let files = verification.payload.files().to_vec();
+    let out_dir = out_dir.to_path_buf();
+    tokio::task::spawn_blocking(move || download_files(&files, &out_dir).map_err(|e| e.to_string()))
+        .await?
+        .map_err(Into::into)
+}
@@ -0,0 +1,73 @@
+//! The failures of the zkVM proving path, by the phase they happen in.
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum ZkvmError {
+    /// The guest program could not be compiled to powdr asm.
+    Compile(String),
+    FixedColumns(String),
+    /// The PIL of the chunk could not be optimized.
+    OptimizedPil(String),
+    Witness(String),
+    Proof(String),
+    /// The verification key or the circom verifier could not be generated.
+    VerifierExport(String),
+    /// The StarkStruct of the chunk is below `--min_security_bits`.
+    InsufficientSecurity(String),
+    Io {
+        context: String,
+        source: std::io::Error,
+    },
+}
+
+impl ZkvmError {
+    /// The phase the error happened in, as reported in the failure outcome.
+    pub fn phase(&self) -> &'static str {
+        match self {
+            ZkvmError::Compile(_) => "compile",
+            ZkvmError::FixedColumns(_) => "fixed_columns",
+            ZkvmError::OptimizedPil(_) => "optimized_pil",
+            ZkvmError::Witness(_) => "witness",
+            ZkvmError::Proof(_) => "proof",
+            ZkvmError::VerifierExport(_) => "verifier_export",
+            ZkvmError::InsufficientSecurity(_) => "security",
+            ZkvmError::Io { .. } => "io",
+        }
+    }
+
+    /// Maps the error list of a powdr pipeline call into the `phase` variant.
+    pub fn pipeline(phase: fn(String) -> Self) -> impl Fn(Vec<String>) -> Self {
+        move |errors| phase(errors.join("; "))
+    }
+
+    /// Maps an I/O error into one saying what was being done.
+    pub fn io(context: impl Into<String>) -> impl FnOnce(std::io::Error) -> Self {
+        let context = context.into();
+        move |source| ZkvmError::Io { context, source }
+    }
+}
+
+impl fmt::Display for ZkvmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZkvmError::Compile(e) => write!(f, "could not compile the guest program: {e}"),
+            ZkvmError::FixedColumns(e) => write!(f, "could not compute the fixed columns: {e}"),
+            ZkvmError::OptimizedPil(e) => write!(f, "could not compute the optimized PIL: {e}"),
+            ZkvmError::Witness(e) => write!(f, "could not generate the witness: {e}"),
+            ZkvmError::Proof(e) => write!(f, "could not compute the proof: {e}"),
+            ZkvmError::VerifierExport(e) => write!(f, "could not generate the verifier: {e}"),
+            ZkvmError::InsufficientSecurity(e) => write!(f, "{e}"),
+            ZkvmError::Io { context, source } => write!(f, "{context}: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for ZkvmError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ZkvmError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Proves the chunks of a manifest in parallel, one workflow per chunk.
+
+use crate::node::{parse_hash, run_transaction};
+use crate::watch::wait_for_result;
+use eigen_gevulot_common::chunks::ChunkManifest;
+use eigen_gevulot_common::fanout::{chunk_job, ChunkState, ChunkStatus, FanoutState};
+use eigen_gevulot_common::workflow::Job;
+use gevulot_node::{rpc_client::RpcClient, types::Hash};
+use libsecp256k1::SecretKey;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+pub struct Fanout {
+    pub client: Arc<RpcClient>,
+    pub key: SecretKey,
+    pub job: Job,
+    pub manifest: ChunkManifest,
+    /// The base URL the chunk files and the `.asm` are published under.
+    pub file_server: String,
+    pub state_file: PathBuf,
+    pub concurrency: usize,
+    pub deadline: Duration,
+    pub poll_interval: Duration,
+}
+
+impl Fanout {
+    /// Submits every chunk that is neither verified nor in flight, waits for
+    /// all of them with at most `concurrency` workflows running, and returns
+    /// the final state.
+    pub async fn run(self) -> Result<FanoutState> {
+        let state = FanoutState::load_or_new(&self.state_file, &self.manifest)?;
+        state.save(&self.state_file)?;
+        let state = Arc::new(Mutex::new(state));
+        let this = Arc::new(self);
+        let permits = Arc::new(Semaphore::new(this.concurrency.max(1)));
+
+        let mut handles = vec![];
+        for chunk in &this.manifest.chunks {
+            let chunk_id = chunk.chunk_id;
+            let current = state.lock().unwrap().chunks[&chunk_id].clone();
+            if current.status == ChunkStatus::Verified {
+                println!("chunk {chunk_id}: already verified");
+                continue;
+            }
+            let (this, state, permits) = (this.clone(), state.clone(), permits.clone());
+            handles.push(tokio::spawn(async move {
+                let _permit = permits
+                    .acquire()
+                    .await
+                    .expect("the semaphore is never closed");
+                this.prove_chunk(chunk_id, current, &state).await
+            }));
+        }
+        for handle in handles {
+            if let Err(e) = handle.await? {
+                println!("{e}");
+            }
+        }
+
+        let state = state.lock().unwrap().clone();
+        Ok(state)
+    }
+
+    async fn prove_chunk(
+        &self,
+        chunk_id: u64,
+        current: ChunkState,
+        state: &Mutex<FanoutState>,
+    ) -> std::result::Result<(), String> {
+        let tx_hash = match current.tx_hash.filter(|_| current.status.is_in_flight()) {
+            Some(tx_hash) => {
+                println!("chunk {chunk_id}: waiting again for {tx_hash}");
+                parse_hash(&tx_hash).map_err(|e| e.to_string())?
+            }
+            None => match self.submit(chunk_id).await {
+                Ok(tx_hash) => {
+                    println!("chunk {chunk_id}: submitted, tx hash:{tx_hash}");
+                    self.update(
+                        state,
+                        chunk_id,
+                        Some(tx_hash.to_string()),
+                        ChunkStatus::Submitted,
+                    )?;
+                    tx_hash
+                }
+                Err(e) => {
+                    let message = e.to_string();
+                    self.update(state, chunk_id, None, ChunkStatus::Failed(message.clone()))?;
+                    return Err(format!("chunk {chunk_id}: submission failed: {message}"));
+                }
+            },
+        };
+
+        let (status, _) =
+            wait_for_result(&self.client, &tx_hash, self.deadline, self.poll_interval)
+                .await
+                .map_err(|e| e.to_string())?;
+        println!("chunk {chunk_id}: {status:?}");
+        self.update(state, chunk_id, Some(tx_hash.to_string()), (&status).into())
+    }
+
+    async fn submit(&self, chunk_id: u64) -> Result<Hash> {
+        let chunk = self
+            .manifest
+            .chunks
+            .iter()
+            .find(|c| c.chunk_id == chunk_id)
+            .ok_or_else(|| format!("chunk {chunk_id} is not in the manifest"))?;
+        let workflow = chunk_job(&self.job, &self.manifest, chunk, &self.file_server)
+            .builder()
+            .build()?;
+        let tx = run_transaction(&workflow, &self.key)?;
+        self.client
+            .send_transaction(&tx)
+            .await
+            .map_err(|e| format!("An error while sending the Run transaction: {e}"))?;
+        Ok(tx.hash)
+    }
+
+    fn update(
+        &self,
+        state: &Mutex<FanoutState>,
+        chunk_id: u64,
+        tx_hash: Option<String>,
+        status: ChunkStatus,
+    ) -> std::result::Result<(), String> {
+        let mut state = state.lock().unwrap();
+        state
+            .chunks
+            .insert(chunk_id, ChunkState { tx_hash, status });
+        state.save(&self.state_file).map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,33 @@
+//! Fetches a transaction or a workflow tree for `tx` and `tx-tree`.
+
+use crate::node::{get_transaction, get_tx_tree, parse_hash};
+use eigen_gevulot_common::inspect::{inspect_tree, InspectedTree, InspectedTx};
+use gevulot_node::{rpc_client::RpcClient, types::Hash};
+use std::collections::HashMap;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+pub async fn tx(client: &RpcClient, hash: &Hash) -> Result<InspectedTx> {
+    Ok(InspectedTx::new(&get_transaction(client, hash).await?))
+}
+
+/// The tree of the Run transaction `hash` with every transaction of it. A
+/// transaction that cannot be fetched is shown as not fetched.
+pub async fn tx_tree(client: &RpcClient, hash: &Hash) -> Result<InspectedTree> {
+    let root = hash.to_string();
+    let tree = get_tx_tree(client, hash).await?;
+    let hashes = match &tree {
+        Some(tree) => tree.hashes().into_iter().map(str::to_string).collect(),
+        None => vec![root.clone()],
+    };
+    let mut txs = HashMap::new();
+    for hash in hashes {
+        match get_transaction(client, &parse_hash(&hash)?).await {
+            Ok(tx) => {
+                txs.insert(hash, tx);
+            }
+            Err(e) => log::warn!("{e}"),
+        }
+    }
+    Ok(inspect_tree(&root, tree.as_ref(), &txs))
+}
@@ -1,9 +1,9 @@
-use clap::Parser;
+use clap::{ArgGroup, Args, Parser, Subcommand};
 use gevulot_node::{
     rpc_client::{RpcClient, RpcClientBuilder},
     types::{
         program::ResourceRequest,
-        transaction::{Payload, ProgramData, ProgramMetadata, Workflow, WorkflowStep},
+        transaction::{Payload, ProgramMetadata},
         Hash, Transaction,
     },
 };
@@ -11,6 +11,20 @@ use gevulot_node::{
 //
 use gevulot_cli::run_exec_command;
 
+use eigen_gevulot_common::chunks::ChunkManifest;
+use eigen_gevulot_common::deployment::Deployment;
+use eigen_gevulot_common::file_server::FileServer;
+use eigen_gevulot_common::harness::{parse_key_path, TaskInput};
+use eigen_gevulot_common::inspect::{render_tree, render_tx};
+use eigen_gevulot_common::profile::{self, shell_quote, Profile};
+use eigen_gevulot_common::progress::WorkflowStatus;
+use eigen_gevulot_common::registry::{self, Registry};
+use eigen_gevulot_common::resources::{self, CircuitSize, Estimate};
+use eigen_gevulot_common::staging;
+use eigen_gevulot_common::stark::StarkParams;
+use eigen_gevulot_common::tx::ProgramData;
+use eigen_gevulot_common::tx::Workflow as CommonWorkflow;
+use eigen_gevulot_common::workflow::Job;
 use libsecp256k1::SecretKey;
 
 use std::{
@@ -19,21 +33,146 @@ use std::{
     sync::Arc,
     time::Duration,
 };
-use tokio::time::sleep;
-
 
+mod chunks;
+mod download;
+mod fanout;
+mod inspect;
+mod node;
+mod watch;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 #[derive(Parser, Debug)]
 #[clap(author = "Gevulot Team", version, about, long_about = None)]
 pub struct ArgConfiguration {
+    /// The JSON-RPC URL of the node; taken from the profile when not given.
+    #[clap(short, long)]
+    pub json_rpc_url: Option<String>,
+    /// The profile to use, see `eigen_gevulot_common::profile`; the file's default when not given.
+    #[clap(long)]
+    profile: Option<String>,
+    /// The profile file; profiles.json by default.
+    #[clap(long = "profiles", value_name = "FILE")]
+    profiles: Option<PathBuf>,
+    /// The registry of the deployed programs, which job files and tasks may name.
+    #[clap(long, default_value = registry::DEFAULT_REGISTRY_FILE, value_name = "FILE")]
+    registry: PathBuf,
+    #[clap(long = "rpctimeout", value_name = "RPC TIMEOUT")]
+    rpc_timeout: Option<u64>,
+    #[clap(subcommand)]
+    command: ConfCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfCommands {
+    /// Send a workflow and wait for the verifier result.
+    Exec(ExecArgs),
+    /// Build the workflow of a job file, send it and wait for the verifier result.
+    Run(RunArgs),
+    /// Deploy a prover and a verifier image and record their program hashes.
+    Deploy(DeployArgs),
+    /// Compile a guest program and write the bootloader-input file of each chunk with a manifest.
+    Chunks {
+        /// The task name, as passed to the prover's `--task_name`.
+        #[clap(long, default_value = "lr")]
+        task_name: String,
+        /// The guest program crate; `program/<task name>` by default.
+        #[clap(long, value_name = "DIR")]
+        program: Option<String>,
+        /// The input data of the guest, as passed to the prover's `--trace_file`.
+        #[clap(long, value_name = "FILE")]
+        trace_file: PathBuf,
+        /// Where the `.asm`, the chunk files and `manifest.json` are written.
+        #[clap(short, long, default_value = "output", value_name = "DIR")]
+        out_dir: String,
+    },
+    /// Prove every chunk of a manifest, one workflow per chunk.
+    Fanout(FanoutArgs),
+    /// Publish local input files and print the `Input` entries referencing them.
+    Stage(StageArgs),
+    /// Print the prover resources recommended for a circuit.
+    Estimate {
+        #[clap(long)]
+        piljson: PathBuf,
+        #[clap(long)]
+        stark_struct: PathBuf,
+    },
+    /// Print the bits of security of a StarkStruct.
+    Security {
+        #[clap(long)]
+        stark_struct: PathBuf,
+        /// Fail unless the conjectured security reaches this many bits.
+        #[clap(long)]
+        min_security_bits: Option<u32>,
+    },
+    /// Download and checksum the files of the Verification leaf of a workflow.
+    Download {
+        /// The tx hash returned by `exec`.
+        #[clap(long, value_name = "TX HASH")]
+        tx: String,
+        /// The directory the files are stored in, at their VM paths.
+        #[clap(short, long, default_value = ".", value_name = "DIR")]
+        out_dir: PathBuf,
+    },
+    /// Print a workflow tree with the decoded result and status of each transaction.
+    TxTree {
+        /// The tx hash returned by `exec`.
+        #[clap(long, value_name = "TX HASH")]
+        tx: String,
+        /// Print the tree as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+    /// Print one transaction with its result data decoded.
+    Tx {
+        #[clap(long, value_name = "TX HASH")]
+        tx: String,
+        /// Print the transaction as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+    /// List, show and promote the programs of the registry.
+    Registry {
+        #[clap(subcommand)]
+        command: RegistryCommand,
+    },
+    /// Print the settings of the selected profile, after the environment and the options.
+    Profile {
+        /// Print them as `export` lines of the EIGEN_GEVULOT_* variables, for the shell scripts.
+        #[clap(long)]
+        shell: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum RegistryCommand {
+    /// List the registered programs; `*` marks the promoted version of each name.
+    List {
+        /// Only the versions of this program name.
+        name: Option<String>,
+        /// Print them as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+    /// Print a registered program as JSON.
+    Show {
+        /// `<name>`, `<name>@<version>` or `<name>@latest`.
+        reference: String,
+    },
+    /// Make a version the one its bare name resolves to.
+    Promote {
+        /// `<name>@<version>` or `<name>@latest`.
+        reference: String,
+    },
+}
+
+#[derive(Args, Debug)]
+struct ExecArgs {
+    /// The key file; taken from the profile when not given.
+    #[clap(short, long)]
+    pub key_file: Option<PathBuf>,
 
-    #[clap(short, long, default_value = "http://localhost:9944")]
-    pub json_rpc_url: String,
-    #[clap(short, long, default_value = "localkey.pki")]
-    pub key_file: PathBuf,
-    
     /// Optional Address of the local http server use by the node to download input file.
     #[clap(
         short,
@@ -52,6 +191,7 @@ pub struct ArgConfiguration {
     ///         {"Output":{"source_program":"Program Hash","file_name":"<file path where the file is written in the VM"}}],
     ///     , ...
     /// }]
+    /// A program (or source_program) may also be a registry reference, eg. `#eigen-gevulot-prover@2`.
     /// Example for proving and verification:
     /// --tasks '[
     /// {"program":"9616d42b0d82c1ed06eab8eaa26680261ad831012bbf3ad8303738a53bf85c7c","cmd_args":[{"name":"--nonce","value":"42"}],"inputs":[{"Input":{"local_path":"witness.txt","vm_path":"/workspace/witness.txt"}}]}
@@ -64,8 +204,171 @@ pub struct ArgConfiguration {
     ///]'
     #[clap(short, long, value_name = "TASK ARRAY")]
     tasks: String,
-    #[clap(long = "rpctimeout", value_name = "RPC TIMEOUT")]
-    rpc_timeout: Option<u64>,
+    /// Seconds to wait for the verifier result before giving up.
+    #[clap(long, default_value_t = 1800, value_name = "SECONDS")]
+    deadline: u64,
+    /// Seconds between two polls of the transaction tree.
+    #[clap(long = "poll-interval", default_value_t = 15, value_name = "SECONDS")]
+    poll_interval: u64,
+}
+
+/// The circuit the prover resources are estimated from.
+#[derive(Args, Debug)]
+struct CircuitArgs {
+    /// The PIL json of the circuit.
+    #[clap(long, requires = "stark_struct")]
+    piljson: Option<PathBuf>,
+    /// The StarkStruct json of the circuit.
+    #[clap(long, requires = "piljson")]
+    stark_struct: Option<PathBuf>,
+}
+
+impl CircuitArgs {
+    fn estimate(&self) -> Result<Option<Estimate>> {
+        match (&self.piljson, &self.stark_struct) {
+            (Some(pil), Some(stark_struct)) => Ok(Some(resources::estimate(&CircuitSize::load(
+                pil,
+                stark_struct,
+            )?)?)),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+struct DeployArgs {
+    /// The key file; taken from the profile when not given.
+    #[clap(short, long)]
+    pub key_file: Option<PathBuf>,
+    #[clap(long, default_value = "prover & verifier")]
+    name: String,
+    /// Where the program hashes are stored.
+    #[clap(short, long, default_value = "deployment.json", value_name = "FILE")]
+    out: PathBuf,
+    /// The prover image, as built by `ops build`.
+    #[clap(long = "prover", value_name = "IMAGE")]
+    prover_img: PathBuf,
+    #[clap(long, default_value = "#eigen-gevulot-prover")]
+    prover_name: String,
+    /// The URL the node downloads the prover image from.
+    #[clap(long, value_name = "URL")]
+    prover_img_url: String,
+    /// The prover CPUs; estimated from the circuit when given, 32 otherwise.
+    #[clap(long)]
+    prover_cpus: Option<u64>,
+    /// The prover memory in MB; estimated from the circuit when given, 65536 otherwise.
+    #[clap(long)]
+    prover_mem: Option<u64>,
+    #[clap(long, default_value_t = 0)]
+    prover_gpus: u64,
+    #[clap(flatten)]
+    circuit: CircuitArgs,
+    /// The verifier image, as built by `ops build`.
+    #[clap(long = "verifier", value_name = "IMAGE")]
+    verifier_img: PathBuf,
+    #[clap(long, default_value = "#eigen-gevulot-verifier")]
+    verifier_name: String,
+    /// The URL the node downloads the verifier image from.
+    #[clap(long, value_name = "URL")]
+    verifier_img_url: String,
+    #[clap(long, default_value_t = 4)]
+    verifier_cpus: u64,
+    /// The verifier memory in MB.
+    #[clap(long, default_value_t = 4096)]
+    verifier_mem: u64,
+    #[clap(long, default_value_t = 0)]
+    verifier_gpus: u64,
+}
+
+#[derive(Args, Debug)]
+#[clap(group(ArgGroup::new("target").required(true).args(["publish_dir", "listen_addr"])))]
+struct StageArgs {
+    /// A file to stage, as `<vm path>=<file>`; repeated for each file.
+    #[clap(long = "input", value_name = "VM_PATH=FILE", value_parser = parse_key_path, required = true)]
+    inputs: Vec<(String, PathBuf)>,
+    /// The directory the http file server serves, eg. /data/http.
+    #[clap(long = "publish-dir", value_name = "DIR")]
+    publish_dir: Option<PathBuf>,
+    /// The base URL the publish directory is served under; taken from the profile when not given.
+    #[clap(long = "file-server", value_name = "URL")]
+    file_server: Option<String>,
+    /// Serve the files from this address until the node has fetched them all.
+    #[clap(long = "listen-addr", value_name = "LOCAL SERVER BIND ADDR")]
+    listen_addr: Option<SocketAddr>,
+    /// The URL the node reaches the served files at; `http://<listen addr>` by default.
+    #[clap(long = "public-url", requires = "listen_addr")]
+    public_url: Option<String>,
+    /// Seconds to serve the files for at most.
+    #[clap(long, default_value_t = 1800, value_name = "SECONDS")]
+    deadline: u64,
+    /// Print the inputs in the `--tasks` format of gevulot-cli instead of as `ProgramData`.
+    #[clap(long = "tasks-format")]
+    tasks_format: bool,
+}
+
+#[derive(Args, Debug)]
+struct FanoutArgs {
+    /// The key file; taken from the profile when not given.
+    #[clap(short, long)]
+    pub key_file: Option<PathBuf>,
+    /// The manifest written by `chunks`.
+    #[clap(long, value_name = "FILE")]
+    manifest: PathBuf,
+    /// The job file of one chunk, where `{task}` and `{chunk_id}` are replaced per chunk.
+    #[clap(long, value_name = "JOB FILE")]
+    job: PathBuf,
+    /// The deployment file written by `deploy`, for the program hashes.
+    #[clap(long, value_name = "FILE")]
+    deployment: Option<PathBuf>,
+    /// The base URL the files of the manifest are uploaded to; taken from the profile when not given.
+    #[clap(long, value_name = "URL")]
+    file_server: Option<String>,
+    /// The file tracking the tx hash and status of every chunk.
+    #[clap(long, default_value = "fanout-state.json", value_name = "FILE")]
+    state: PathBuf,
+    /// The number of chunk workflows running at once.
+    #[clap(long, default_value_t = 4)]
+    concurrency: usize,
+    /// Seconds to wait for the verifier result of each chunk.
+    #[clap(long, default_value_t = 1800, value_name = "SECONDS")]
+    deadline: u64,
+    /// Seconds between two polls of a transaction tree.
+    #[clap(long = "poll-interval", default_value_t = 15, value_name = "SECONDS")]
+    poll_interval: u64,
+}
+
+#[derive(Args, Debug)]
+struct RunArgs {
+    /// The key file; taken from the profile when not given.
+    #[clap(short, long)]
+    pub key_file: Option<PathBuf>,
+    /// The job file, see `eigen_gevulot_common::workflow`.
+    #[clap(long, value_name = "JOB FILE")]
+    job: PathBuf,
+    /// The deployment file written by `deploy`, for the program hashes.
+    #[clap(long, value_name = "FILE")]
+    deployment: Option<PathBuf>,
+    /// The prover program hash or registry reference, instead of the one in the job file.
+    #[clap(long, value_name = "PROGRAM")]
+    prover: Option<String>,
+    /// The verifier program hash or registry reference, instead of the one in the job file.
+    #[clap(long, value_name = "PROGRAM")]
+    verifier: Option<String>,
+    /// Serve the local input files of the job from this address, instead of the job's file server.
+    #[clap(long = "listen-addr", value_name = "LOCAL SERVER BIND ADDR")]
+    listen_addr: Option<SocketAddr>,
+    /// The URL the node reaches the served files at; `http://<listen addr>` by default.
+    #[clap(long = "public-url", requires = "listen_addr")]
+    public_url: Option<String>,
+    /// Print the workflow instead of sending it.
+    #[clap(long = "dry-run")]
+    dry_run: bool,
+    /// Seconds to wait for the verifier result before giving up.
+    #[clap(long, default_value_t = 1800, value_name = "SECONDS")]
+    deadline: u64,
+    /// Seconds between two polls of the transaction tree.
+    #[clap(long = "poll-interval", default_value_t = 15, value_name = "SECONDS")]
+    poll_interval: u64,
 }
 
 #[tokio::main]
@@ -73,104 +376,553 @@ async fn main() -> Result<()> {
     env_logger::init();
     log::info!("====ZKVM-Gevulot e2e-test =======");
     let cfg = ArgConfiguration::parse();
+    // Only the commands talking to a node or a file server read the profiles,
+    // so a broken profile file does not get in the way of the offline ones.
+    let settings = || {
+        let cli = Profile {
+            json_rpc_url: cfg.json_rpc_url.clone(),
+            ..Default::default()
+        };
+        profile::resolve(cfg.profiles.as_deref(), cfg.profile.as_deref(), cli)
+    };
     //let client = RpcClientBuilder::default().build(cfg.json_rpc_url)?;
 
-    let mut client_builder = RpcClientBuilder::default();
-    if let Some(rpc_timeout) = cfg.rpc_timeout {
-        client_builder = client_builder.request_timeout(Duration::from_secs(rpc_timeout));
+    match cfg.command {
+        ConfCommands::Exec(args) => exec(&settings()?, &cfg.registry, cfg.rpc_timeout, args).await,
+        ConfCommands::Run(args) => {
+            run_job(&settings()?, &cfg.registry, cfg.rpc_timeout, args).await
+        }
+        ConfCommands::Deploy(args) => {
+            deploy(&settings()?, &cfg.registry, cfg.rpc_timeout, args).await
+        }
+        ConfCommands::Chunks {
+            task_name,
+            program,
+            trace_file,
+            out_dir,
+        } => {
+            let program = program.unwrap_or_else(|| format!("program/{task_name}"));
+            let (manifest, path) =
+                chunks::generate_chunk_files(&task_name, &program, &trace_file, &out_dir)?;
+            for chunk in &manifest.chunks {
+                println!("chunk {}: {} {}", chunk.chunk_id, chunk.file, chunk.blake3);
+            }
+            println!(
+                "{} chunks of {} ({} {}, {} {}) written to {}",
+                manifest.chunk_count,
+                task_name,
+                manifest.asm_file,
+                manifest.asm_blake3,
+                manifest.trace_file,
+                manifest.trace_blake3,
+                path.display()
+            );
+            Ok(())
+        }
+        ConfCommands::Fanout(args) => {
+            fanout(&settings()?, &cfg.registry, cfg.rpc_timeout, args).await
+        }
+        ConfCommands::Stage(args) => stage(settings, args),
+        ConfCommands::Estimate {
+            piljson,
+            stark_struct,
+        } => {
+            let estimate = resources::estimate(&CircuitSize::load(&piljson, &stark_struct)?)?;
+            println!("{}", serde_json::to_string_pretty(&estimate)?);
+            Ok(())
+        }
+        ConfCommands::Security {
+            stark_struct,
+            min_security_bits,
+        } => {
+            let params = StarkParams::load(&stark_struct)?;
+            let level = match min_security_bits {
+                Some(min_bits) => params.check_security(min_bits)?,
+                None => params.security(),
+            };
+            println!("{}", serde_json::to_string_pretty(&level)?);
+            Ok(())
+        }
+        ConfCommands::TxTree { tx, json } => {
+            let client = build_client(&settings()?.json_rpc_url(), cfg.rpc_timeout);
+            let tree = inspect::tx_tree(&client, &node::parse_hash(&tx)?).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tree)?);
+            } else {
+                print!("{}", render_tree(&tree));
+            }
+            Ok(())
+        }
+        ConfCommands::Tx { tx, json } => {
+            let client = build_client(&settings()?.json_rpc_url(), cfg.rpc_timeout);
+            let tx = inspect::tx(&client, &node::parse_hash(&tx)?).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tx)?);
+            } else {
+                print!("{}", render_tx(&tx));
+            }
+            Ok(())
+        }
+        ConfCommands::Registry { command } => registry_command(&cfg.registry, command),
+        ConfCommands::Profile { shell } => {
+            let settings = settings()?;
+            if shell {
+                let key_file = settings.key_file();
+                let key_file = key_file.to_string_lossy();
+                println!(
+                    "export {}={}",
+                    profile::JSON_RPC_URL_ENV,
+                    shell_quote(&settings.json_rpc_url())
+                );
+                println!(
+                    "export {}={}",
+                    profile::KEY_FILE_ENV,
+                    shell_quote(&key_file)
+                );
+                if let Some(file_server) = &settings.file_server {
+                    println!(
+                        "export {}={}",
+                        profile::FILE_SERVER_ENV,
+                        shell_quote(file_server)
+                    );
+                }
+            } else {
+                let resolved = Profile {
+                    json_rpc_url: Some(settings.json_rpc_url()),
+                    key_file: Some(settings.key_file()),
+                    file_server: settings.file_server.clone(),
+                };
+                println!("{}", serde_json::to_string_pretty(&resolved)?);
+            }
+            Ok(())
+        }
+        ConfCommands::Download { tx, out_dir } => {
+            let client = build_client(&settings()?.json_rpc_url(), cfg.rpc_timeout);
+            let tx_hash = node::parse_hash(&tx)?;
+            match download::download_results(&client, &tx_hash, &out_dir).await {
+                Ok(paths) => {
+                    for path in paths {
+                        println!("{}", path.display());
+                    }
+                    println!("Download complete, all checksums match.");
+                    Ok(())
+                }
+                Err(err) => {
+                    println!("An error occurs during the download of {tx_hash}: {err}");
+                    std::process::exit(1);
+                }
+            }
+        }
     }
-    let client = client_builder
-        .build(cfg.json_rpc_url)
-        .expect("build rpc client");
-  
+}
+
+async fn exec(
+    settings: &Profile,
+    registry: &Path,
+    rpc_timeout: Option<u64>,
+    args: ExecArgs,
+) -> Result<()> {
+    let json_rpc_url = &settings.json_rpc_url();
+    let key_file = args.key_file.clone().unwrap_or_else(|| settings.key_file());
+    let tasks = Registry::load_or_default(registry)?.resolve_tasks(&args.tasks)?;
+    let client = build_client(json_rpc_url, rpc_timeout);
+    // `run_exec_command` takes its client by value; the watcher needs its own.
+    let watch_client = build_client(json_rpc_url, rpc_timeout);
+
     //let bs = std::fs::read(cfg.key_file)?;
     //let key = SecretKey::parse_slice(&bs)?;
 
-   
-
-    //Prover hash:  e78145a32b208a22b34e03cc6a6146d35683801cc97309ab86ae3ec1f0f26d70  
+    //Prover hash:  e78145a32b208a22b34e03cc6a6146d35683801cc97309ab86ae3ec1f0f26d70
     //Verifier hash:  3032e67af5a5d4bc058515956911570417d0481183a7c753b907b11a8f97a45f
 
     //let prover_hash   = Hash::from("e78145a32b208a22b34e03cc6a6146d35683801cc97309ab86ae3ec1f0f26d70");
     //let verifier_hash = Hash::from("3032e67af5a5d4bc058515956911570417d0481183a7c753b907b11a8f97a45f");
 
     log::info!("====before proving  =======");
-    match run_exec_command(client, cfg.key_file, cfg.tasks, cfg.listen_addr).await {
-        Ok(tx_hash) => println!("Programs send to execution correctly. Tx hash:{tx_hash}"),
-        Err(err) => println!("An error occurs during send execution Tx :{err}"),
+    let tx_hash = match run_exec_command(client, key_file, tasks, args.listen_addr).await {
+        Ok(tx_hash) => {
+            println!("Programs send to execution correctly. Tx hash:{tx_hash}");
+            node::parse_hash(&tx_hash.to_string())?
+        }
+        Err(err) => {
+            println!("An error occurs during send execution Tx :{err}");
+            std::process::exit(1);
+        }
+    };
+
+    report_result(&watch_client, &tx_hash, args.deadline, args.poll_interval).await
+}
+
+async fn deploy(
+    settings: &Profile,
+    registry: &Path,
+    rpc_timeout: Option<u64>,
+    args: DeployArgs,
+) -> Result<()> {
+    let json_rpc_url = &settings.json_rpc_url();
+    let key_file = args.key_file.clone().unwrap_or_else(|| settings.key_file());
+    let estimate = args.circuit.estimate()?;
+    let prover_resources = ResourceRequest {
+        cpus: args.prover_cpus.or(estimate.map(|e| e.cpus)).unwrap_or(32),
+        mem: args.prover_mem.or(estimate.map(|e| e.mem)).unwrap_or(65536),
+        gpus: args.prover_gpus,
+    };
+    if let Some(estimate) = &estimate {
+        println!(
+            "Estimated prover resources: {} CPUs, {} MB memory",
+            estimate.cpus, estimate.mem
+        );
+        for warning in resources::check(&node::view(&prover_resources)?, estimate) {
+            println!("WARNING: {warning}");
+        }
+    }
+    let prover = from_img_file_to_metadata(
+        &args.prover_name,
+        &args.prover_img,
+        &args.prover_img_url,
+        prover_resources,
+    )?;
+    let verifier = from_img_file_to_metadata(
+        &args.verifier_name,
+        &args.verifier_img,
+        &args.verifier_img_url,
+        ResourceRequest {
+            cpus: args.verifier_cpus,
+            mem: args.verifier_mem,
+            gpus: args.verifier_gpus,
+        },
+    )?;
+
+    let bs = std::fs::read(&key_file)?;
+    let key = SecretKey::parse_slice(&bs)?;
+    let tx = Transaction::new(
+        Payload::Deploy {
+            name: args.name.clone(),
+            prover: prover.clone(),
+            verifier: verifier.clone(),
+        },
+        &key,
+    );
+
+    println!("Start prover / verifier deployment");
+    let client = build_client(json_rpc_url, rpc_timeout);
+    if let Err(err) = client.send_transaction(&tx).await {
+        println!("An error occurs during the deployment: {err}");
+        std::process::exit(1);
     }
+    println!("Prover / Verifier deployed correctly.");
+    println!("Prover hash:{}", prover.hash);
+    println!("Verifier hash:{}", verifier.hash);
+    println!("Tx Hash:{}", tx.hash);
 
-    sleep(Duration::from_secs(360)).await;
+    let deployment = Deployment {
+        name: args.name,
+        tx_hash: tx.hash.to_string(),
+        prover: node::view(&prover)?,
+        verifier: node::view(&verifier)?,
+    };
+    deployment.save(&args.out)?;
+    println!("The program hashes are stored in {}", args.out.display());
 
+    let mut programs = Registry::load_or_default(registry)?;
+    for program in [&deployment.prover, &deployment.verifier] {
+        let registered = programs.register(program, &deployment.tx_hash);
+        println!(
+            "Registered {} as {}",
+            registered.hash,
+            registered.reference()
+        );
+    }
+    programs.save(registry)?;
     Ok(())
 }
 
+async fn fanout(
+    settings: &Profile,
+    registry: &Path,
+    rpc_timeout: Option<u64>,
+    args: FanoutArgs,
+) -> Result<()> {
+    let json_rpc_url = &settings.json_rpc_url();
+    let key_file = args.key_file.clone().unwrap_or_else(|| settings.key_file());
+    let mut job = Job::load(&args.job)?;
+    if let Some(path) = &args.deployment {
+        let deployment = Deployment::load(path)?;
+        job.prover.program = deployment.prover.hash;
+        job.verifier.program = deployment.verifier.hash;
+    }
+    Registry::load_or_default(registry)?.resolve_job(&mut job)?;
+    let bs = std::fs::read(&key_file)?;
+    let key = SecretKey::parse_slice(&bs)?;
+
+    let state = fanout::Fanout {
+        client: Arc::new(build_client(json_rpc_url, rpc_timeout)),
+        key,
+        job,
+        manifest: ChunkManifest::load(&args.manifest)?,
+        file_server: match args.file_server {
+            Some(file_server) => file_server,
+            None => settings.file_server()?,
+        },
+        state_file: args.state.clone(),
+        concurrency: args.concurrency,
+        deadline: Duration::from_secs(args.deadline),
+        poll_interval: Duration::from_secs(args.poll_interval),
+    }
+    .run()
+    .await?;
 
-async fn send_proving_task(
-    client: &RpcClient,
-    key: &SecretKey,
-    nonce: u64,
-    prover_hash: &Hash,
-    verifier_hash: &Hash,
-) -> Result<Hash> {
-    let proving_step = WorkflowStep {
-        program: *prover_hash,
-        args: vec!["--nonce".to_string(), nonce.to_string()],
-        inputs: vec![],
+    for (chunk_id, chunk) in &state.chunks {
+        println!(
+            "chunk {chunk_id}: {:?} {}",
+            chunk.status,
+            chunk.tx_hash.as_deref().unwrap_or("-")
+        );
+    }
+    println!("The state is stored in {}", args.state.display());
+    std::process::exit(if state.all_verified() { 0 } else { 1 });
+}
+
+async fn run_job(
+    settings: &Profile,
+    registry: &Path,
+    rpc_timeout: Option<u64>,
+    args: RunArgs,
+) -> Result<()> {
+    let json_rpc_url = &settings.json_rpc_url();
+    let key_file = args.key_file.clone().unwrap_or_else(|| settings.key_file());
+    let mut job = Job::load(&args.job)?;
+    if let Some(path) = &args.deployment {
+        let deployment = Deployment::load(path)?;
+        job.prover.program = deployment.prover.hash;
+        job.verifier.program = deployment.verifier.hash;
+    }
+    if let Some(prover) = args.prover {
+        job.prover.program = prover;
+    }
+    if let Some(verifier) = args.verifier {
+        job.verifier.program = verifier;
+    }
+    Registry::load_or_default(registry)?.resolve_job(&mut job)?;
+    if job.file_server.is_none() {
+        job.file_server = settings.file_server.clone();
+    }
+    let mut builder = job.builder();
+    let mut file_server = None;
+    if let Some(addr) = args.listen_addr {
+        let public_url = args.public_url.unwrap_or_else(|| format!("http://{addr}"));
+        let mut server = FileServer::new(public_url);
+        builder = builder.publish_files(|path| server.add(path))?;
+        file_server = Some((server, addr));
+    }
+    let workflow = builder.build()?;
+    if args.dry_run {
+        println!("{}", serde_json::to_string_pretty(&workflow)?);
+        return Ok(());
+    }
+    // Served until the node has fetched every file, or the command ends.
+    let _file_server = match file_server {
+        Some((server, addr)) if !server.is_empty() => Some(server.start(&addr.to_string())?),
+        _ => None,
     };
 
-    let verifying_step = WorkflowStep {
-        program: *verifier_hash,
-        args: vec!["--nonce".to_string(), nonce.to_string()],
-        inputs: vec![ProgramData::Output {
-            source_program: *prover_hash,
-            file_name: "/workspace/proof.dat".to_string(),
-        }],
+    let bs = std::fs::read(&key_file)?;
+    let key = SecretKey::parse_slice(&bs)?;
+    let client = build_client(json_rpc_url, rpc_timeout);
+    let tx_hash = match send_workflow(&client, &key, &workflow).await {
+        Ok(tx_hash) => {
+            println!("Programs send to execution correctly. Tx hash:{tx_hash}");
+            tx_hash
+        }
+        Err(err) => {
+            println!("An error occurs during send execution Tx :{err}");
+            std::process::exit(1);
+        }
     };
+    report_result(&client, &tx_hash, args.deadline, args.poll_interval).await
+}
 
-    let tx = Transaction::new(
-        Payload::Run {
-            workflow: Workflow {
-                steps: vec![proving_step, verifying_step],
-            },
-        },
-        key,
-    );
+fn registry_command(path: &Path, command: RegistryCommand) -> Result<()> {
+    let mut registry = Registry::load_or_default(path)?;
+    match command {
+        RegistryCommand::List { name, json } => {
+            let programs = registry.list(name.as_deref());
+            if json {
+                println!("{}", serde_json::to_string_pretty(&programs)?);
+                return Ok(());
+            }
+            for program in programs {
+                let marker = if registry.is_promoted(program) {
+                    '*'
+                } else {
+                    ' '
+                };
+                let resources = program
+                    .resource_requirements
+                    .as_ref()
+                    .map(|r| format!("{} CPUs, {} MB, {} GPUs", r.cpus, r.mem, r.gpus))
+                    .unwrap_or_default();
+                println!(
+                    "{marker} {} {} [{resources}] tx {} at {}",
+                    program.reference(),
+                    program.hash,
+                    program.tx_hash,
+                    program.deployed_at
+                );
+            }
+        }
+        RegistryCommand::Show { reference } => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(registry.find(&reference)?)?
+            );
+        }
+        RegistryCommand::Promote { reference } => {
+            let program = registry.promote(&reference)?;
+            registry.save(path)?;
+            println!(
+                "{} now resolves to {} ({})",
+                program.name,
+                program.reference(),
+                program.hash
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `settings` is only resolved when the file server is not given.
+fn stage(settings: impl FnOnce() -> Result<Profile>, args: StageArgs) -> Result<()> {
+    let print = |inputs: &[ProgramData]| -> Result<()> {
+        let json = if args.tasks_format {
+            serde_json::to_string_pretty(&inputs.iter().map(TaskInput::from).collect::<Vec<_>>())?
+        } else {
+            serde_json::to_string_pretty(inputs)?
+        };
+        println!("{json}");
+        Ok(())
+    };
+
+    if let Some(dir) = &args.publish_dir {
+        let base_url = match &args.file_server {
+            Some(file_server) => file_server.clone(),
+            None => settings()?.file_server()?,
+        };
+        let inputs = staging::stage(&args.inputs, |file| {
+            staging::publish_to_dir(file, dir, &base_url)
+        })?;
+        return print(&inputs);
+    }
+    let addr = args
+        .listen_addr
+        .ok_or("either --publish-dir or --listen-addr is needed")?;
+    let public_url = args
+        .public_url
+        .clone()
+        .unwrap_or_else(|| format!("http://{addr}"));
+    let mut server = FileServer::new(public_url);
+    let inputs = staging::stage(&args.inputs, |file| server.add(file))?;
+    print(&inputs)?;
+
+    let server = server.start(&addr.to_string())?;
+    if server.wait_fetched(Duration::from_secs(args.deadline)) {
+        eprintln!("Every input file has been fetched.");
+    } else {
+        eprintln!(
+            "{} input files were not fetched after {} s.",
+            server.pending(),
+            args.deadline
+        );
+    }
+    server.shutdown();
+    Ok(())
+}
+
+/// Waits for the verifier result of `tx_hash`, prints it and exits with the
+/// code of the final status.
+async fn report_result(
+    client: &RpcClient,
+    tx_hash: &Hash,
+    deadline: u64,
+    poll_interval: u64,
+) -> Result<()> {
+    let (status, progress) = watch::wait_for_result(
+        client,
+        tx_hash,
+        Duration::from_secs(deadline),
+        Duration::from_secs(poll_interval),
+    )
+    .await?;
 
+    if let Some(outcome) = progress.proof_outcome() {
+        match &outcome.phase {
+            Some(phase) => println!(
+                "prover: {} (code {}, in {})",
+                outcome.message, outcome.code, phase
+            ),
+            None => println!("prover: {} (code {})", outcome.message, outcome.code),
+        }
+    }
+    match &status {
+        WorkflowStatus::Verified => println!("The proof of {tx_hash} is verified."),
+        WorkflowStatus::Rejected(reason) => {
+            println!("The proof of {tx_hash} is rejected: {reason}")
+        }
+        _ => println!("No verifier result for {tx_hash} after {deadline} s."),
+    }
+    std::process::exit(status.exit_code());
+}
+
+fn build_client(json_rpc_url: &str, rpc_timeout: Option<u64>) -> RpcClient {
+    let mut client_builder = RpcClientBuilder::default();
+    if let Some(rpc_timeout) = rpc_timeout {
+        client_builder = client_builder.request_timeout(Duration::from_secs(rpc_timeout));
+    }
+    client_builder
+        .build(json_rpc_url.to_string())
+        .expect("build rpc client")
+}
+
+async fn send_workflow(
+    client: &RpcClient,
+    key: &SecretKey,
+    workflow: &CommonWorkflow,
+) -> Result<Hash> {
+    let tx = node::run_transaction(workflow, key)?;
     client
         .send_transaction(&tx)
         .await
-        .expect("send_transaction");
-
+        .map_err(|e| format!("An error while sending the Run transaction: {e}"))?;
     Ok(tx.hash)
 }
 
-fn from_img_file_to_metadata(img_file: &Path, img_file_url: &str) -> ProgramMetadata {
+fn from_img_file_to_metadata(
+    name: &str,
+    img_file: &Path,
+    img_file_url: &str,
+    resources: ResourceRequest,
+) -> Result<ProgramMetadata> {
     let mut hasher = blake3::Hasher::new();
-    let fd = std::fs::File::open(img_file).expect("open");
-    hasher.update_reader(fd).expect("checksum");
+    let fd = std::fs::File::open(img_file)
+        .map_err(|e| format!("opening image {}: {e}", img_file.display()))?;
+    hasher.update_reader(fd)?;
     let checksum = hasher.finalize();
 
     let file_name = img_file
         .file_name()
-        .expect("file name")
-        .to_str()
-        .unwrap()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("image {} has no file name", img_file.display()))?
         .to_string();
 
     let mut program = ProgramMetadata {
-        name: file_name.clone(),
+        name: name.to_string(),
         hash: Hash::default(),
         image_file_name: file_name,
         image_file_url: img_file_url.to_string(),
         image_file_checksum: checksum.to_string(),
-        resource_requirements: Some(ResourceRequest {
-            cpus: 1,
-            mem: 128,
-            gpus: 0,
-        }),
+        resource_requirements: Some(resources),
     };
 
     program.update_hash();
-    program
+    Ok(program)
 }
@@ -0,0 +1,94 @@
+//! Node queries, returned as the JSON views of `eigen_gevulot_common::tx`.
+
+use eigen_gevulot_common::progress::NO_ROOT_TX;
+use eigen_gevulot_common::tx::{self, TxOutput, TxTree};
+use gevulot_node::{
+    rpc_client::RpcClient,
+    types::{
+        transaction::{Payload, ProgramData, Workflow, WorkflowStep},
+        Hash, Transaction,
+    },
+};
+use libsecp256k1::SecretKey;
+use serde::{de::DeserializeOwned, Serialize};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Parses a hex transaction or program hash.
+pub fn parse_hash(hash: &str) -> Result<Hash> {
+    let bytes = hex::decode(hash.trim()).map_err(|e| format!("hash decoding error:{e}"))?;
+    if bytes.len() != 32 {
+        return Err(format!("hash {hash} is not 32 bytes long").into());
+    }
+    Ok((&bytes[..]).into())
+}
+
+/// The transaction tree under `hash`, or `None` while the node has no
+/// result for it yet.
+pub async fn get_tx_tree(client: &RpcClient, hash: &Hash) -> Result<Option<TxTree>> {
+    match client.get_tx_tree(hash).await {
+        Ok(tree) => Ok(Some(view(&tree)?)),
+        Err(e) if e.to_string().contains(NO_ROOT_TX) => Ok(None),
+        Err(e) => Err(format!("An error while fetching transaction tree: {e}").into()),
+    }
+}
+
+pub async fn get_transaction(client: &RpcClient, hash: &Hash) -> Result<TxOutput> {
+    let tx = client
+        .get_transaction(hash)
+        .await
+        .map_err(|e| format!("An error while fetching transaction {hash}: {e}"))?;
+    view(&tx)
+}
+
+/// The signed Run transaction of a workflow built by
+/// `eigen_gevulot_common::workflow`.
+pub fn run_transaction(workflow: &tx::Workflow, key: &SecretKey) -> Result<Transaction> {
+    let steps = workflow
+        .steps
+        .iter()
+        .map(|step| {
+            Ok(WorkflowStep {
+                program: parse_hash(&step.program)?,
+                args: step.args.clone(),
+                inputs: step
+                    .inputs
+                    .iter()
+                    .map(program_data)
+                    .collect::<Result<_>>()?,
+            })
+        })
+        .collect::<Result<_>>()?;
+    Ok(Transaction::new(
+        Payload::Run {
+            workflow: Workflow { steps },
+        },
+        key,
+    ))
+}
+
+fn program_data(data: &tx::ProgramData) -> Result<ProgramData> {
+    Ok(match data {
+        tx::ProgramData::Input {
+            file_name,
+            file_url,
+            checksum,
+        } => ProgramData::Input {
+            file_name: file_name.clone(),
+            file_url: file_url.clone(),
+            checksum: checksum.clone(),
+        },
+        tx::ProgramData::Output {
+            source_program,
+            file_name,
+        } => ProgramData::Output {
+            source_program: parse_hash(source_program)?,
+            file_name: file_name.clone(),
+        },
+    })
+}
+
+/// Converts a node type to its JSON view in `eigen_gevulot_common::tx`.
+pub fn view<T: Serialize, V: DeserializeOwned>(value: &T) -> Result<V> {
+    Ok(serde_json::from_value(serde_json::to_value(value)?)?)
+}
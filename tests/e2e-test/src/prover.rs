@@ -9,7 +9,7 @@ use std::io;
 use std::io::prelude::*;
 
 //from lib.rs
-use anyhow::Result;
+use error::ZkvmError;
 use powdr::backend::BackendType;
 use powdr::number::{DegreeType, FieldElement, GoldilocksField};
 use powdr::riscv::continuations::{rust_continuations, rust_continuations_dry_run};
@@ -20,65 +20,71 @@ use starky::{
     merklehash::MerkleTreeGL,
     pil2circom,
     stark_setup::StarkSetup,
-    types::{StarkStruct, Step},
+    types::StarkStruct,
 };
 use std::fs::{self, create_dir_all /*, remove_dir_all*/};
 use std::io::BufWriter;
 use std::path::Path;
 use std::time::Instant;
 
+mod error;
+
+type Result<T> = std::result::Result<T, ZkvmError>;
+
 const TEST_CHANNEL: u32 = 1;
 fn generate_witness_and_prove<F: FieldElement>(
     mut pipeline: Pipeline<F>,
-) -> Result<(), Vec<String>> {
-    let start = Instant::now();
+    timings: &Timings,
+) -> Result<()> {
     log::debug!("Generating witness...");
-    pipeline.compute_witness().unwrap();
-    let duration = start.elapsed();
-    log::debug!("Generating witness took: {:?}", duration);
+    timings
+        .time("witness", || pipeline.compute_witness().map(|_| ()))
+        .map_err(ZkvmError::pipeline(ZkvmError::Witness))?;
 
-    let start = Instant::now();
     log::debug!("Proving ...");
-
     pipeline = pipeline.with_backend(BackendType::EStarkStarky, Some("stark_gl".to_string()));
-    pipeline.compute_proof().unwrap();
-    let duration = start.elapsed();
-    log::debug!("Proving took: {:?}", duration);
+    timings
+        .time("proof", || pipeline.compute_proof().map(|_| ()))
+        .map_err(ZkvmError::pipeline(ZkvmError::Proof))?;
     Ok(())
 }
 
+/// Writes the verification key of the pipeline to `vkey_file` and its circom
+/// verifier to `writer`, with the `StarkStruct` the eStark backend proves
+/// the trace with, which is returned.
 fn generate_verifier<F: FieldElement, W: std::io::Write>(
     mut pipeline: Pipeline<F>,
     mut writer: W,
-) -> Result<()> {
+    vkey_file: &Path,
+) -> Result<StarkParams> {
     let buf = Vec::new();
     let mut vw = BufWriter::new(buf);
     pipeline = pipeline.with_backend(BackendType::EStarkStarky, Some("stark_gl".to_string()));
-    pipeline.export_verification_key(&mut vw).unwrap();
+    pipeline
+        .export_verification_key(&mut vw)
+        .map_err(ZkvmError::pipeline(ZkvmError::VerifierExport))?;
     log::debug!("Export verification key done");
-    let mut setup: StarkSetup<MerkleTreeGL> = serde_json::from_slice(&vw.into_inner()?)?;
+    let vk = vw
+        .into_inner()
+        .map_err(|e| ZkvmError::io("buffering the verification key")(e.into_error()))?;
+    fs::write(vkey_file, &vk).map_err(ZkvmError::io(format!("writing {}", vkey_file.display())))?;
+    let mut setup: StarkSetup<MerkleTreeGL> = serde_json::from_slice(&vk).map_err(|e| {
+        ZkvmError::VerifierExport(format!("the verification key is not a StarkSetup: {}", e))
+    })?;
     log::debug!("Load StarkSetup done");
 
-    let pil = pipeline.optimized_pil().unwrap();
+    let pil = pipeline
+        .optimized_pil()
+        .map_err(ZkvmError::pipeline(ZkvmError::VerifierExport))?;
 
-    let degree = pil.degree();
-    assert!(degree > 1);
-    let n_bits = (DegreeType::BITS - (degree - 1).leading_zeros()) as usize;
-    let n_bits_ext = n_bits + 1;
+    let n_bits = trace_bits(pil.degree()).map_err(ZkvmError::VerifierExport)?;
 
-    let steps = (2..=n_bits_ext)
-        .rev()
-        .step_by(4)
-        .map(|b| Step { nBits: b })
-        .collect();
-
-    let params = StarkStruct {
-        nBits: n_bits,
-        nBitsExt: n_bits_ext,
-        nQueries: 2,
-        verificationHashType: "GL".to_owned(),
-        steps,
-    };
+    // The backend takes no StarkStruct, so this is the one it proves with.
+    let stark_params = StarkParams::estark(n_bits);
+    log::debug!("StarkStruct of the verifier: {:?}", stark_params);
+    let params: StarkStruct = serde_json::to_value(&stark_params)
+        .and_then(serde_json::from_value)
+        .map_err(|e| ZkvmError::VerifierExport(format!("the StarkStruct is not starky's: {}", e)))?;
 
     // generate circom
     let opt = pil2circom::StarkOption {
@@ -97,9 +103,35 @@ fn generate_verifier<F: FieldElement, W: std::io::Write>(
             &mut setup.program,
             &opt,
         )
-        .unwrap();
-        writer.write_fmt(format_args!("{}", str_ver))?;
+        .map_err(|e| ZkvmError::VerifierExport(e.to_string()))?;
+        writer
+            .write_fmt(format_args!("{}", str_ver))
+            .map_err(ZkvmError::io("writing the circom verifier"))?;
     }
+    Ok(stark_params)
+}
+
+/// log2 of the number of rows of a trace of `degree`.
+fn trace_bits(degree: DegreeType) -> std::result::Result<usize, String> {
+    if degree <= 1 {
+        return Err(format!("the trace degree {} is too small", degree));
+    }
+    Ok((DegreeType::BITS - (degree - 1).leading_zeros()) as usize)
+}
+
+/// Fails unless the `StarkStruct` the eStark backend will prove the chunk
+/// with gives at least `min_bits` bits of conjectured security. The
+/// optimized PIL computed for the degree is kept by the pipeline for the
+/// verifier export.
+fn check_security<F: FieldElement>(pipeline: &mut Pipeline<F>, min_bits: u32) -> Result<()> {
+    let pil = pipeline
+        .optimized_pil()
+        .map_err(ZkvmError::pipeline(ZkvmError::OptimizedPil))?;
+    let n_bits = trace_bits(pil.degree()).map_err(ZkvmError::OptimizedPil)?;
+    let level = StarkParams::estark(n_bits)
+        .check_security(min_bits)
+        .map_err(|e| ZkvmError::InsufficientSecurity(e.to_string()))?;
+    log::info!("security: {:?}", level);
     Ok(())
 }
 
@@ -114,8 +146,7 @@ pub fn zkvm_execute_and_prove(task: &str, suite_json: String, output_path: &str)
         &Runtime::base().with_poseidon(),
         with_bootloader,
     )
-    .ok_or_else(|| vec!["could not compile rust".to_string()])
-    .unwrap();
+    .ok_or_else(|| ZkvmError::Compile(format!("could not compile program/{task}")))?;
 
     let mut pipeline = Pipeline::<GoldilocksField>::default()
         .with_output(output_path.into(), true)
@@ -126,7 +157,9 @@ pub fn zkvm_execute_and_prove(task: &str, suite_json: String, output_path: &str)
     log::debug!("Computing fixed columns...");
     let start = Instant::now();
 
-    pipeline.compute_fixed_cols().unwrap();
+    pipeline
+        .compute_fixed_cols()
+        .map_err(ZkvmError::pipeline(ZkvmError::FixedColumns))?;
 
     let duration = start.elapsed();
     log::debug!("Computing fixed columns took: {:?}", duration);
@@ -158,7 +191,12 @@ pub fn zkvm_execute_and_prove(task: &str, suite_json: String, output_path: &str)
     log::debug!("Running witness generation...");
     let start = Instant::now();
 
-    rust_continuations(pipeline, generate_witness_and_prove, bootloader_inputs).unwrap();
+    let timings = Timings::default();
+    rust_continuations(
+        pipeline,
+        |p| generate_witness_and_prove(p, &timings),
+        bootloader_inputs,
+    )?;
 
     let duration = start.elapsed();
     log::debug!("Witness generation took: {:?}", duration);
@@ -166,58 +204,6 @@ pub fn zkvm_execute_and_prove(task: &str, suite_json: String, output_path: &str)
     Ok(())
 }
 
-pub fn zkvm_generate_chunks(
-    workspace: &str,
-    suite_json: &String,
-    output_path: &str,
-) -> Result<Vec<(Vec<GoldilocksField>, u64)>> {
-    log::debug!("Compiling Rust...");
-    let force_overwrite = true;
-    let with_bootloader = true;
-    let (asm_file_path, asm_contents) = compile_rust::<GoldilocksField>(
-        workspace,
-        Path::new(output_path),
-        force_overwrite,
-        &Runtime::base().with_poseidon(),
-        with_bootloader,
-    )
-    .ok_or_else(|| vec!["could not compile rust".to_string()])
-    .unwrap();
-
-    let mut pipeline = Pipeline::<GoldilocksField>::default()
-        .with_output(output_path.into(), true)
-        .from_asm_string(asm_contents.clone(), Some(asm_file_path.clone()))
-        .with_prover_inputs(Default::default())
-        .add_data(TEST_CHANNEL, suite_json);
-
-    log::debug!("Running powdr-riscv executor in fast mode...");
-
-    /*
-    let (trace, _mem) = powdr::riscv_executor::execute::<GoldilocksField>(
-        &asm_contents,
-        powdr::riscv_executor::MemoryState::new(),
-        pipeline.data_callback().unwrap(),
-        &default_input(&[]),
-        powdr::riscv_executor::ExecMode::Fast,
-    );
-
-    log::debug!("Trace length: {}", trace.len);
-    */
-    log::debug!("Running powdr-riscv executor in trace mode for continuations...");
-    let start = Instant::now();
-
-    let bootloader_inputs = rust_continuations_dry_run(&mut pipeline);
-
-    let duration = start.elapsed();
-    log::debug!(
-        "Trace executor took: {:?}, input size: {:?}",
-        duration,
-        bootloader_inputs.len()
-    );
-
-    Ok(bootloader_inputs)
-}
-
 pub fn zkvm_prove_only(
     task: &str,
     suite_json: &String,
@@ -225,40 +211,53 @@ pub fn zkvm_prove_only(
     start_of_shutdown_routine: u64,
     i: usize,
     output_path: &str,
+    min_security_bits: Option<u32>,
+    timings: &Timings,
 ) -> Result<()> {
     log::debug!("Compiling Rust...");
     let asm_file_path = Path::new(output_path).join(format!("{}.asm", task));
 
-    let pipeline = Pipeline::<GoldilocksField>::default()
+    let mut pipeline = Pipeline::<GoldilocksField>::default()
         .with_output(output_path.into(), true)
         .from_asm_file(asm_file_path.clone())
         .with_prover_inputs(Default::default())
         .add_data(TEST_CHANNEL, suite_json);
 
+    if let Some(min_bits) = min_security_bits {
+        check_security(&mut pipeline, min_bits)?;
+    }
+
     log::debug!("Running witness generation and proof computation...");
     let start = Instant::now();
 
-    
-  
-
     //TODO: if we clone it, we lost the information gained from this function
     rust_continuation(
         task,
         pipeline.clone(),
-        generate_witness_and_prove,
+        |p| generate_witness_and_prove(p, timings),
         bootloader_input,
         start_of_shutdown_routine,
         i,
-    )
-    .unwrap();
- 
+        timings,
+    )?;
+
     let verifier_file = Path::new(output_path).join(format!("{}_chunk_{}.circom", task, i));
     log::debug!(
         "Running circom verifier generation to {:?}...",
         verifier_file
     );
-    let f = fs::File::create(verifier_file)?;
-    generate_verifier(pipeline, f).unwrap();
+    let vkey_file = Path::new(output_path).join(format!("{}_chunk_{}.vkey.json", task, i));
+    let mut circom = Vec::new();
+    let stark_params =
+        timings.time("circom", || generate_verifier(pipeline, &mut circom, &vkey_file))?;
+    // Written only once generated, so a failure leaves no empty verifier behind.
+    fs::write(&verifier_file, &circom)
+        .map_err(ZkvmError::io(format!("writing {}", verifier_file.display())))?;
+    // Record the parameters, so the verifier of the proof knows what was used.
+    let stark_file = Path::new(output_path).join(format!("{}_chunk_{}.stark_struct.json", task, i));
+    stark_params
+        .save(&stark_file)
+        .map_err(|e| ZkvmError::VerifierExport(format!("writing {}: {}", stark_file.display(), e)))?;
 
     let duration = start.elapsed();
     log::debug!(
@@ -269,16 +268,17 @@ pub fn zkvm_prove_only(
     Ok(())
 }
 
-pub fn rust_continuation<F: FieldElement, PipelineCallback, E>(
+pub fn rust_continuation<F: FieldElement, PipelineCallback>(
     task: &str,
     mut pipeline: Pipeline<F>,
     pipeline_callback: PipelineCallback,
     bootloader_inputs: Vec<F>,
     start_of_shutdown_routine: u64,
     i: usize,
-) -> Result<(), E>
+    timings: &Timings,
+) -> Result<()>
 where
-    PipelineCallback: Fn(Pipeline<F>) -> Result<(), E>,
+    PipelineCallback: Fn(Pipeline<F>) -> Result<()>,
 {
     // Here the fixed columns most likely will have been computed already,
     // in which case this will be a no-op.
@@ -286,10 +286,14 @@ where
    // std::fs::write("/workspace/test.log", b"rust_continuation():222222 \n").unwrap();
 
  
-    pipeline.compute_fixed_cols().unwrap();
+    timings
+        .time("fixed_columns", || pipeline.compute_fixed_cols().map(|_| ()))
+        .map_err(ZkvmError::pipeline(ZkvmError::FixedColumns))?;
 
     // we can assume optimized_pil has been computed
-   let length = pipeline.compute_optimized_pil().unwrap().degree();
+   let length = timings
+        .time("optimized_pil", || pipeline.compute_optimized_pil().map(|pil| pil.degree()))
+        .map_err(ZkvmError::pipeline(ZkvmError::OptimizedPil))?;
 
    // std::fs::write("/workspace/test.log", b"rust_continuation():3333333 \n").unwrap();
 
@@ -303,14 +307,20 @@ where
 
    
     // now we should do
-    let parent_path = pipeline.output_dir().unwrap();
+    let parent_path = pipeline.output_dir().ok_or_else(|| {
+        ZkvmError::io("the chunk directory")(io::Error::new(
+            io::ErrorKind::NotFound,
+            "the pipeline has no output directory",
+        ))
+    })?;
     let chunk_dir = parent_path.join(name);
     //remove_dir_all(&chunk_dir).unwrap();
-    create_dir_all(&chunk_dir).unwrap();
+    create_dir_all(&chunk_dir)
+        .map_err(ZkvmError::io(format!("creating {}", chunk_dir.display())))?;
     let pipeline = pipeline.with_output(chunk_dir, true);
 
     let jump_to_shutdown_routine = (0..length)
-        .map(|i| (i == start_of_shutdown_routine - 1).into())
+        .map(|i| (i + 1 == start_of_shutdown_routine).into())
         .collect();
 
     let pipeline = pipeline.add_external_witness_values(vec![
@@ -332,7 +342,7 @@ where
 
 
 /////////////////////Parameter parse
-#[derive(Debug, Parser, Default)]
+#[derive(Debug, Parser, Default, Serialize)]
 #[command(about, version, no_binary_name(true))]
 //#[derive(Parser, Debug)]
 //#[command(author, version = "0.1.6", about, long_about = None)]
@@ -351,32 +361,60 @@ struct Cli {
     #[arg(long = "output_path", default_value = "/workspace")] //must use the default value!!
     output_path: String,
 
+    /// Refuse to prove when the StarkStruct the eStark backend derives for
+    /// the chunk gives fewer bits of conjectured security.
+    #[arg(long = "min_security_bits")]
+    min_security_bits: Option<u32>,
+
 }
 
 
+use eigen_gevulot_common::args::parse_task_args;
+use eigen_gevulot_common::bootloader::BootloaderInput;
+use eigen_gevulot_common::outcome::{split_existing, Outcome, Status};
+use eigen_gevulot_common::report::{task_failed, Report, Timings};
+use eigen_gevulot_common::stark::StarkParams;
+use eigen_gevulot_common::task::{self, workspace_file, TaskOutput};
 use gevulot_shim::{Task, TaskResult};
+use serde::Serialize;
 
 type gResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 fn main()-> gResult<()>  {
-   gevulot_shim::run(run_task)
+    if task::is_local() {
+        return task::run_local(prove);
+    }
+    gevulot_shim::run(run_task)
 }
 
 fn run_task(task: Task) -> gResult<TaskResult> {
+    let output = prove(&task.args)?;
+    task.result(output.data, output.files)
+}
+
+fn prove(task_args: &[String]) -> gResult<TaskOutput> {
 
 
     env_logger::init();
 
-    println!("0xEigenLabs prover : task.args: {:?}", &task.args);
+    println!("0xEigenLabs prover : task.args: {:?}", task_args);
 
     //let args =  Cli::parse();
-    let args =  Cli::parse_from(&task.args);
+    let args = match parse_task_args::<Cli>(task_args) {
+        Ok(args) => args,
+        Err(e) => {
+            log::info!("The prover has invalid arguments: {}", e);
+            let mut report = Report::new(&task_args);
+            let outcome = Outcome::failure(Status::InvalidArguments, e.to_string());
+            return task_failed(&mut report, outcome, &[]);
+        }
+    };
 
     log::info!("parameters: trace_file:{};  bootloader input file:{}",args.trace_file, args.bi_file);
     log::info!("parameters: task_name:{};  number_chunk:{}",args.task_name, args.chunk_id);
 
-    let mut log_file = fs::File::create("/workspace/test.log")?;
-    write!(log_file, "trace_file:{}\n",  &args.trace_file);
+    let mut log_file = fs::File::create(workspace_file("test.log"))?;
+    write!(log_file, "trace_file:{}\n",  &args.trace_file)?;
     write!(log_file, "bi_file:{}\n",  &args.bi_file)?;
     write!(log_file, "task_name:{}\n",  &args.task_name)?;
     write!(log_file, "number_chunk:{}\n",  &args.chunk_id)?;
@@ -384,30 +422,53 @@ fn run_task(task: Task) -> gResult<TaskResult> {
 
   
 
-    //generate proof
-    let suite_json = fs::read_to_string(args.trace_file).unwrap();
-
-    let workspace = format!("program/{}", args.task_name);
-
-    let mut f = fs::File::open(&args.bi_file).unwrap();
-    let metadata = fs::metadata(&args.bi_file).unwrap();
-    let file_size = metadata.len() as usize;
+    let mut report = Report::new(&args);
+    report.add_input(&args.trace_file);
+    report.add_input(&args.bi_file);
+    report.add_input(&format!("{}/{}.asm", &args.output_path, &args.task_name));
 
-    assert!(file_size % 8 == 0);
-    // read the start_of_shutdown_routine
-    let mut buffer = [0u8; 8];
-    f.read_exact(&mut buffer).unwrap();
-    let start_of_shutdown_routine: u64 = u64::from_le_bytes(buffer);
+    //generate proof
+    let suite_json = match fs::read_to_string(&args.trace_file) {
+        Ok(suite_json) => suite_json,
+        Err(e) => {
+            let message = format!("trace file {}: {}", args.trace_file, e);
+            log::info!("The prover has error: {}", message);
+            write!(log_file, "The prover has error: {}\n", message)?;
+            let outcome = Outcome::failure(Status::InvalidInput, message);
+            return task_failed(&mut report, outcome, &[]);
+        }
+    };
 
-    let file_size = file_size - 8;
-    let mut buffer = vec![0; file_size];
-    f.read_exact(&mut buffer).unwrap();
-    let mut bi = vec![GoldilocksField::default(); file_size / 8];
-    bi.iter_mut().zip(buffer.chunks(8)).for_each(|(out, bin)| {
-                *out = GoldilocksField::from_bytes_le(bin);
-            });
-     write!(log_file, "start_of_shutdown_routine:{}\n",  &start_of_shutdown_routine)?;
+    let circom_file = format!("{}/{}_chunk_{}.circom",&args.output_path, &args.task_name, &args.chunk_id);
+    let proof_file = format!("{}/{}_chunk_{}/{}_proof.bin",&args.output_path, &args.task_name, &args.chunk_id, &args.task_name);
+    let stark_file = format!("{}/{}_chunk_{}.stark_struct.json",&args.output_path, &args.task_name, &args.chunk_id);
+    let vkey_file = format!("{}/{}_chunk_{}.vkey.json",&args.output_path, &args.task_name, &args.chunk_id);
+    let outputs = vec![proof_file, circom_file, stark_file, vkey_file];
+
+    let bootloader_input = match BootloaderInput::read(Path::new(&args.bi_file))
+        .and_then(|bi| bi.check_task(&args.task_name, args.chunk_id as u64).map(|_| bi))
+    {
+        Ok(bi) => bi,
+        Err(e) => {
+            let message = format!("bootloader input {}: {}", args.bi_file, e);
+            log::info!("The prover has error: {}", message);
+            write!(log_file, "The prover has error: {}\n", message)?;
+            let outcome = Outcome::failure(Status::InvalidInput, message);
+            return task_failed(&mut report, outcome, &outputs);
+        }
+    };
+    if bootloader_input.is_legacy() {
+        log::info!("{} is in the raw layout without a header", args.bi_file);
+    }
+    let start_of_shutdown_routine = bootloader_input.start_of_shutdown_routine;
+    let bi: Vec<GoldilocksField> = bootloader_input
+        .elements
+        .into_iter()
+        .map(GoldilocksField::from)
+        .collect();
+    write!(log_file, "start_of_shutdown_routine:{}\n",  &start_of_shutdown_routine)?;
 
+    let timings = Timings::default();
     let exec_result = zkvm_prove_only(
                 &args.task_name,
                 &suite_json,
@@ -415,36 +476,44 @@ fn run_task(task: Task) -> gResult<TaskResult> {
                 start_of_shutdown_routine,
                 args.chunk_id,
                 &args.output_path,
+                args.min_security_bits,
+                &timings,
             );
+    report.phases = timings.into_phases();
 
 
-    match exec_result {
-        Err(x) => {
-            log::info!("The prover has error: {}", x);
-            write!(log_file, "The prover has error: {}\n", x)?;
-        }
-        _ => write!(log_file, "The prover executes successfully.\n")?,
-    };
+    // The generated proof is in /workspace/lr_chunk_0/lr_proof.bin,
+    // the verifier in /workspace/lr_chunk_0.circom.
+
+    if let Err(x) = exec_result {
+        log::info!("The prover has error: {}", x);
+        write!(log_file, "The prover has error: {}\n", x)?;
+        let status = match x {
+            ZkvmError::InsufficientSecurity(_) => Status::InsufficientSecurity,
+            _ => Status::ProverFailed,
+        };
+        let outcome = Outcome::failure(status, x.to_string()).in_phase(x.phase());
+        return task_failed(&mut report, outcome, &outputs);
+    }
 
+    let (_, missing) = split_existing(&outputs);
+    if !missing.is_empty() {
+        let message = format!("the prover did not write {}", missing.join(", "));
+        log::info!("The prover has error: {}", message);
+        write!(log_file, "The prover has error: {}\n", message)?;
+        let outcome = Outcome::failure(Status::MissingOutput, message);
+        return task_failed(&mut report, outcome, &outputs);
+    }
+
+    write!(log_file, "The prover executes successfully.\n")?;
     log::info!("The prover executes successfully");
 
-    // Write generated proof to a file.
-    // /workspace/lr_chunk_0/lr_proof.bin.
-    // /workspace/lr_chunk_0.circom
+    let outcome = Outcome::success();
+    report.finish(&outcome, &workspace_file("report.json"))?;
 
-    let circom_file = format!("{}/{}_chunk_{}.circom",&args.output_path, &args.task_name, &args.chunk_id);
-    let proof_file = format!("{}/{}_chunk_{}/{}_proof.bin",&args.output_path, &args.task_name, &args.chunk_id, &args.task_name);
-     
-
-    //return three files for Verifier
-    //let tmp_path = "/workspace/lr_chunk_0";
-    //fs::create_dir_all(&tmp_path).unwrap();
-    //std::fs::write("/workspace/lr_chunk_0/lr_proof.bin", b"this is a proof a.");
-    //std::fs::write("/workspace/lr_chunk_0.circom", b"this is a circom .");
-    //write!(log_file, "circom out:{}\n",  &circom_file)?;
-    //write!(log_file, "proof out:{}\n",  &proof_file)?; 
-    //write!("/workspace/test.log", "proof out:{}\n",  )?;
-    //task.result(vec![], vec![String::from("/workspace/lr_chunk_0/lr_proof.bin"),String::from("/workspace/lr_chunk_0.circom"),String::from("/workspace/test.log")])
-    task.result(vec![], vec![String::from(proof_file),String::from(circom_file),String::from("/workspace/test.log")])
-    
-}
\ No newline at end of file
+    //return the proof, the circom verifier, the log and the report for Verifier
+    let mut files = outputs;
+    files.push(workspace_file("test.log"));
+    files.push(workspace_file("report.json"));
+    Ok(TaskOutput::new(outcome.to_bytes(), files))
+}
@@ -1,51 +1,207 @@
 extern crate clap;
 use clap::{command, Parser};
 
-
-
-
+use starky::{
+    merklehash::MerkleTreeGL, stark_gen::StarkProof, stark_setup::StarkSetup,
+    stark_verify::stark_verify, transcript::TranscriptGL, types::StarkStruct,
+};
+use std::fs;
 use std::time::Instant;
-use std::fs::create_dir_all;
-use std::fs::File;
-use std::io::Write;
-
 
+use eigen_gevulot_common::args::parse_task_args;
+use eigen_gevulot_common::outcome::Verdict;
+use eigen_gevulot_common::report::task_verdict;
+use eigen_gevulot_common::stark::StarkParams;
+use eigen_gevulot_common::task::{self, TaskOutput};
 use gevulot_shim::{Task, TaskResult};
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-/////////////////////Parameter parse
 #[derive(Debug, Parser, Default)]
 #[command(about, version, no_binary_name(true))]
 struct Cli {
-
-    #[arg(short, long = "proof_file", default_value = "/workspace/lr_chunk_0/lr_proof.bin")]
+    /// The chunk proof written by the prover.
+    #[arg(
+        short,
+        long = "proof_file",
+        default_value = "/workspace/lr_chunk_0/lr_proof.bin"
+    )]
     proof_file: String,
-    #[arg(short, long = "circom_file", default_value = "/workspace/lr_chunk_0.circom")]
+    /// The circom verifier of the chunk, returned to the client with the proof.
+    #[arg(
+        short,
+        long = "circom_file",
+        default_value = "/workspace/lr_chunk_0.circom"
+    )]
     circom_file: String,
-    
+    /// The verification key the prover exported next to the circom verifier.
+    #[arg(long = "vkey_file", default_value = "/workspace/lr_chunk_0.vkey.json")]
+    vkey_file: String,
+    /// The StarkStruct the prover recorded for the chunk; only its `nBits`
+    /// is taken, the rest must be the eStark backend's.
+    #[arg(
+        long = "stark_struct",
+        default_value = "/workspace/lr_chunk_0.stark_struct.json"
+    )]
+    stark_struct: String,
+    /// The blake3 of a verification key of the chunk circuit obtained outside
+    /// this workflow; without it, the key forwarded by the prover is trusted.
+    #[arg(long = "expected_vkey_checksum")]
+    expected_vkey_checksum: Option<String>,
+    /// Reject proofs whose StarkStruct gives fewer bits of conjectured security.
+    #[arg(long = "min_security_bits")]
+    min_security_bits: Option<u32>,
 }
 
-fn main()-> Result<()>  {
-   gevulot_shim::run(run_task)
+impl Cli {
+    /// The files of the prover returned to the client with the verdict.
+    fn outputs(&self) -> Vec<String> {
+        vec![
+            self.proof_file.clone(),
+            self.circom_file.clone(),
+            self.vkey_file.clone(),
+            self.stark_struct.clone(),
+        ]
+    }
+}
+
+fn main() -> Result<()> {
+    if task::is_local() {
+        return task::run_local(verify_task);
+    }
+    gevulot_shim::run(run_task)
 }
 
 fn run_task(task: Task) -> Result<TaskResult> {
+    let output = verify_task(&task.args)?;
+    task.result(output.data, output.files)
+}
+
+fn verify_task(task_args: &[String]) -> Result<TaskOutput> {
+    env_logger::init();
+    println!("verifier : task.args: {:?}", task_args);
 
-    //env_logger::init();
-    println!("verifier : task.args: {:?}", &task.args);
-    let args =  Cli::parse_from(&task.args);
-   //let log_path = "/workspace/lr_chunk_0";
-    //create_dir_all(&log_path).unwrap();
-    //let mut log_file = File::create("/workspace/test_v.log")?;
-    //write!(log_file, "proof file::{}\n",  &args.proof_file)?;
-    //write!(log_file, "circom file::{}\n",  &args.circom_file)?;
+    let args = match parse_task_args::<Cli>(task_args) {
+        Ok(args) => args,
+        Err(e) => {
+            let verdict = Verdict {
+                verified: false,
+                reason: format!("the verifier has invalid arguments: {e}"),
+                publics: Vec::new(),
+            };
+            // The files of the chunk are at their default paths, if anywhere.
+            let outputs = parse_task_args::<Cli>(&[])
+                .map(|defaults| defaults.outputs())
+                .unwrap_or_default();
+            return Ok(task_verdict(&verdict, &outputs));
+        }
+    };
+    log::info!(
+        "parameters: proof file:{}; vkey file:{}; stark_struct:{}",
+        args.proof_file,
+        args.vkey_file,
+        args.stark_struct
+    );
 
-  
-    
-    //In this test, the verifier does nothing. 
+    let start = Instant::now();
+    let verdict = match check_security(&args) {
+        Err(e) => Verdict {
+            verified: false,
+            reason: format!("the chunk proof is rejected: {e}"),
+            publics: Vec::new(),
+        },
+        Ok(()) => match verify(&args) {
+            Ok((true, publics)) => Verdict {
+                verified: true,
+                reason: "the chunk proof is valid".to_string(),
+                publics,
+            },
+            Ok((false, _)) => Verdict {
+                verified: false,
+                reason: "the chunk proof is invalid".to_string(),
+                publics: Vec::new(),
+            },
+            Err(e) => Verdict {
+                verified: false,
+                reason: format!("the chunk proof could not be verified: {e}"),
+                publics: Vec::new(),
+            },
+        },
+    };
+    log::info!("verification took {:?}: {:?}", start.elapsed(), verdict);
 
+    //return the files generated by the prover to the gevulot's client.
+    Ok(task_verdict(&verdict, &args.outputs()))
+}
+
+/// The StarkStruct of the chunk proof, as recorded by the prover.
+fn stark_params(args: &Cli) -> Result<StarkParams> {
+    // The backend proves with the StarkStruct it derives from the trace
+    // degree, whatever the prover recorded.
+    let recorded = StarkParams::load(args.stark_struct.as_ref())?;
+    let stark_params = StarkParams::estark(recorded.nBits);
+    if recorded != stark_params {
+        return Err(format!(
+            "{} is not the StarkStruct of the eStark backend for 2^{} rows",
+            args.stark_struct, recorded.nBits
+        )
+        .into());
+    }
+    Ok(stark_params)
+}
+
+/// Fails when `--min_security_bits` is given and the StarkStruct of the
+/// chunk proof gives fewer bits of conjectured security.
+fn check_security(args: &Cli) -> Result<()> {
+    if let Some(min_bits) = args.min_security_bits {
+        let level = stark_params(args)?.check_security(min_bits)?;
+        log::info!("security: {:?}", level);
+    }
+    Ok(())
+}
+
+/// Checks the chunk proof against the verification key of the chunk prover,
+/// and returns whether it holds with the public outputs of the proof.
+fn verify(args: &Cli) -> Result<(bool, Vec<String>)> {
+    let stark_params = stark_params(args)?;
+    let stark_struct: StarkStruct = serde_json::from_value(serde_json::to_value(&stark_params)?)?;
+
+    let vkey = fs::read(&args.vkey_file)?;
+    if let Some(expected) = &args.expected_vkey_checksum {
+        let checksum = blake3::hash(&vkey).to_hex();
+        if !checksum.eq_ignore_ascii_case(expected) {
+            return Err(format!(
+                "{} has blake3 {checksum}, not the expected {expected}",
+                args.vkey_file
+            )
+            .into());
+        }
+    }
+    let setup: StarkSetup<MerkleTreeGL> =
+        serde_json::from_slice(&vkey).map_err(|e| format!("parsing {}: {e}", args.vkey_file))?;
+    let proof: StarkProof<MerkleTreeGL> = serde_json::from_slice(&fs::read(&args.proof_file)?)
+        .map_err(|e| format!("parsing {}: {e}", args.proof_file))?;
+    let publics = public_values(&proof.publics)?;
+
+    let verified = stark_verify::<MerkleTreeGL, TranscriptGL>(
+        &proof,
+        &setup.const_root,
+        &setup.starkinfo,
+        &stark_struct,
+        &setup.program,
+    )?;
+    Ok((verified, publics))
+}
 
-      //return  the files generated by the prover  to the gevulot's client.
-      task.result(vec![1,2,3,4,5,6,7,8,9], vec![String::from(&args.circom_file),vec![String::from(&args.proof_file),String::from("/workspace/test.log")])
+/// The public values of a proof, as written in its JSON.
+fn public_values<T: serde::Serialize>(publics: &[T]) -> Result<Vec<String>> {
+    publics
+        .iter()
+        .map(|value| {
+            Ok(match serde_json::to_value(value)? {
+                serde_json::Value::String(s) => s,
+                other => other.to_string(),
+            })
+        })
+        .collect()
 }
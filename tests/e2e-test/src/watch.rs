@@ -0,0 +1,61 @@
+//! Waits for the result of a submitted workflow by polling its transaction tree.
+
+use crate::node::{get_transaction, get_tx_tree, parse_hash};
+use eigen_gevulot_common::progress::{WorkflowProgress, WorkflowStatus};
+use gevulot_node::{rpc_client::RpcClient, types::Hash};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Polls the tree of `tx_hash` every `interval` until the verifier result
+/// arrives or `deadline` has passed.
+pub async fn wait_for_result(
+    client: &RpcClient,
+    tx_hash: &Hash,
+    deadline: Duration,
+    interval: Duration,
+) -> Result<(WorkflowStatus, WorkflowProgress)> {
+    let start = Instant::now();
+    let mut progress = WorkflowProgress::default();
+    let mut seen = HashSet::new();
+    let mut last_status = WorkflowStatus::Pending;
+
+    loop {
+        match get_tx_tree(client, tx_hash).await {
+            Ok(Some(tree)) => {
+                for hash in tree.hashes().into_iter().skip(1) {
+                    if seen.contains(hash) {
+                        continue;
+                    }
+                    match get_transaction(client, &parse_hash(hash)?).await {
+                        Ok(tx) => {
+                            seen.insert(hash.to_string());
+                            println!("{} transaction {hash} recorded", tx.payload.kind());
+                            progress.record(tx);
+                        }
+                        Err(e) => log::warn!("{e}"),
+                    }
+                }
+            }
+            Ok(None) => log::debug!("no result for {tx_hash} yet"),
+            Err(e) => log::warn!("{e}"),
+        }
+
+        let status = progress.status();
+        if status != last_status {
+            println!("workflow {tx_hash}: {status:?}");
+            last_status = status.clone();
+        }
+        if status.is_final() {
+            return Ok((status, progress));
+        }
+
+        let elapsed = start.elapsed();
+        if elapsed >= deadline {
+            return Ok((WorkflowStatus::TimedOut, progress));
+        }
+        sleep(interval.min(deadline - elapsed)).await;
+    }
+}
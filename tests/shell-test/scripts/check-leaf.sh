@@ -1 +1 @@
-gevulot-cli --jsonurl http://api.devnet.gevulot.com:9944 get-tx $1
+gevulot-cli --jsonurl "${EIGEN_GEVULOT_RPC_URL:-http://api.devnet.gevulot.com:9944}" get-tx $1
@@ -1 +1 @@
-gevulot-cli --jsonurl http://api.devnet.gevulot.com:9944 print-tx-tree $1
+gevulot-cli --jsonurl "${EIGEN_GEVULOT_RPC_URL:-http://api.devnet.gevulot.com:9944}" print-tx-tree $1
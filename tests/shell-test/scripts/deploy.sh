@@ -1,15 +1,16 @@
-gevulot-cli --jsonurl "http://api.devnet.gevulot.com:9944" --keyfile my-local-key.pki \
+FILE_SERVER=${EIGEN_GEVULOT_FILE_SERVER:-http://4.145.88.10:8080}
+gevulot-cli --jsonurl "${EIGEN_GEVULOT_RPC_URL:-http://api.devnet.gevulot.com:9944}" --keyfile "${EIGEN_GEVULOT_KEY_FILE:-my-local-key.pki}" \
 	deploy \
 	--name " prover & verifier" \
 	--prover $1 \
 	--provername '#eigen-gevulot-prover' \
-	--proverimgurl 'http://4.145.88.10:8080/prover' \
+	--proverimgurl "$FILE_SERVER/prover" \
 	--provercpus 32 \
 	--provermem 65536 \
 	--provergpus 0 \
 	--verifier $2 \
 	--verifiername '#eigen-gevulot-verifier' \
-	--verifierimgurl 'http://4.145.88.10:8080/verifier' \
+	--verifierimgurl "$FILE_SERVER/verifier" \
 	--verifiercpus 4  \
 	--verifiermem 4096  \
 	--verifiergpus 0
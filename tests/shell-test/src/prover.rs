@@ -2,15 +2,14 @@ extern crate clap;
 use clap::{command, Parser};
 
 
+use serde::Serialize;
 use starky::prove::stark_prove;
-use std::time::Instant;
-use std::fs;
 
 use std::fs::File;
 use std::io::Write;
 
 
-#[derive(Debug, Parser, Default)]
+#[derive(Debug, Parser, Default, Serialize)]
 #[command(about, version, no_binary_name(true))]
 struct Cli {
     #[arg(short, long = "stark_stuct", default_value = "stark_struct.json")]
@@ -36,30 +35,57 @@ struct Cli {
         default_value = "273030697313060285579891744179749754319274977764"
     )]
     prover_addr: String,
+    /// Prove even when the VM has less memory than the circuit is estimated to need.
+    #[arg(long = "skip_memory_check", action= clap::ArgAction::SetTrue)]
+    skip_memory_check: bool,
+    /// Refuse to prove when the StarkStruct gives fewer bits of conjectured security.
+    #[arg(long = "min_security_bits")]
+    min_security_bits: Option<u32>,
 }
 
-//use gevulot_common::WORKSPACE_PATH;
+use eigen_gevulot_common::args::parse_task_args;
+use eigen_gevulot_common::outcome::{split_existing, Outcome, Status};
+use eigen_gevulot_common::report::{task_failed, Report, Timings};
+use eigen_gevulot_common::resources::{check_available_memory, CircuitSize};
+use eigen_gevulot_common::stark::StarkParams;
+use eigen_gevulot_common::task::{self, workspace_file, TaskOutput};
 use gevulot_shim::{Task, TaskResult};
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 fn main()-> Result<()>  {
-   gevulot_shim::run(run_task)
+    if task::is_local() {
+        return task::run_local(prove);
+    }
+    gevulot_shim::run(run_task)
 }
 
 fn run_task(task: Task) -> Result<TaskResult> {
+    let output = prove(&task.args)?;
+    task.result(output.data, output.files)
+}
+
+fn prove(task_args: &[String]) -> Result<TaskOutput> {
 
     env_logger::init();
  
-    println!("0xEigenLabs prover : task.args: {:?}", &task.args);
+    println!("0xEigenLabs prover : task.args: {:?}", task_args);
     
-    let args =  Cli::parse_from(&task.args);
+    let args = match parse_task_args::<Cli>(task_args) {
+        Ok(args) => args,
+        Err(e) => {
+            log::info!("The prover has invalid arguments: {}", e);
+            let mut report = Report::new(&task_args);
+            let outcome = Outcome::failure(Status::InvalidArguments, e.to_string());
+            return task_failed(&mut report, outcome, &[]);
+        }
+    };
 
     log::info!("parameters: proof file:{}; circom fiel:{}",args.zkin,args.circom_file);
     log::info!("parameters: args.stark_struct:{} ; args.piljson:{}; args.const_pols:{}", args.stark_struct, args.piljson, args.const_pols);
     log::info!("parameters: norm_stage:{} ; args.skip_main:{} ; args.agg_stage:{}", args.norm_stage, args.skip_main, args.agg_stage);
 
-    let mut log_file = File::create("/workspace/test.log")?;
+    let mut log_file = File::create(workspace_file("test.log"))?;
     write!(log_file, "stark_struct:{}\n",  &args.stark_struct)?;
     write!(log_file, "piljson:{}\n",  &args.piljson)?;
     write!(log_file, "const_pols:{}\n",  &args.const_pols)?;
@@ -67,49 +93,83 @@ fn run_task(task: Task) -> Result<TaskResult> {
     write!(log_file, "proof:{}\n",  &args.zkin)?;
     write!(log_file, "circom:{}\n", &args.circom_file)?;
 
-   
-    let exec_result = stark_prove(
-        &args.stark_struct,
-        &args.piljson,
-        //args.norm_stage,  //The current gevulot release version doesn't support bool in its "cmd_args",eg. {"name":"--norm_stage","value": ""}. The engineer gives a patch for the bug :
-                            //crates/cli/src/lib.rs  +138
-                            /*let step = WorkflowStep {
-                                program: (&(hex::decode(args.program)
-                                    .map_err(|err| format!("program decoding hash error:{err}"))?)[..])
-                                    .into(),
-                                args: args
-                                    .cmd_args
-                                    .into_iter()
-                                    .flat_map(<[String; 2]>::from)
-                                    .filter(|x| !x.is_empty())  //fix the bug which does not support bool in the "cmd_args" .
-                                    .collect(),
-                                inputs: input_data,
-                            }; */
-        true,
-        args.skip_main,
-        args.agg_stage,
-        &args.const_pols,
-        &args.cm_pols,
-        &args.circom_file,
-        &args.zkin,
-        &args.prover_addr,
-    );
-
-    
-    match exec_result {
-        Err(x) => {
-            log::info!("The prover has error: {}", x);
-            write!(log_file, "The prover has error: {}\n", x)?;
+    let mut report = Report::new(&args);
+    for input in [&args.stark_struct, &args.piljson, &args.const_pols, &args.cm_pols] {
+        report.add_input(input);
+    }
+
+    let outputs = vec![args.zkin.clone(), args.circom_file.clone()];
+
+    if let Some(min_bits) = args.min_security_bits {
+        let checked = StarkParams::load(args.stark_struct.as_ref())
+            .and_then(|params| params.check_security(min_bits));
+        match checked {
+            Ok(level) => log::info!("security: {:?}", level),
+            Err(e) => {
+                log::info!("The prover has error: {}", e);
+                write!(log_file, "The prover has error: {}\n", e)?;
+                let outcome = Outcome::failure(Status::InsufficientSecurity, e.to_string());
+                return task_failed(&mut report, outcome, &outputs);
+            }
         }
-        _ => write!(log_file, "The prover executes successfully.\n")?,
-    };
-
+    }
+
+    if !args.skip_memory_check {
+        match CircuitSize::load(args.piljson.as_ref(), args.stark_struct.as_ref()) {
+            Ok(size) => {
+                if let Err(message) = check_available_memory(&size) {
+                    log::info!("The prover has error: {}", message);
+                    write!(log_file, "The prover has error: {}\n", message)?;
+                    let outcome = Outcome::failure(Status::InsufficientMemory, message);
+                    return task_failed(&mut report, outcome, &outputs);
+                }
+            }
+            Err(e) => log::warn!("skipping the memory check, the circuit size is unknown: {}", e),
+        }
+    }
+
+    let timings = Timings::default();
+    let exec_result = timings.time("proof", || {
+        stark_prove(
+            &args.stark_struct,
+            &args.piljson,
+            args.norm_stage,
+            args.skip_main,
+            args.agg_stage,
+            &args.const_pols,
+            &args.cm_pols,
+            &args.circom_file,
+            &args.zkin,
+            &args.prover_addr,
+        )
+    });
+    report.phases = timings.into_phases();
+
+    if let Err(x) = exec_result {
+        log::info!("The prover has error: {}", x);
+        write!(log_file, "The prover has error: {}\n", x)?;
+        let outcome = Outcome::failure(Status::ProverFailed, x.to_string());
+        return task_failed(&mut report, outcome, &outputs);
+    }
+
+    let (_, missing) = split_existing(&outputs);
+    if !missing.is_empty() {
+        let message = format!("the prover did not write {}", missing.join(", "));
+        log::info!("The prover has error: {}", message);
+        write!(log_file, "The prover has error: {}\n", message)?;
+        let outcome = Outcome::failure(Status::MissingOutput, message);
+        return task_failed(&mut report, outcome, &outputs);
+    }
+
+    write!(log_file, "The prover executes successfully.\n")?;
     log::info!("The prover executes successfully");
 
-    // Write generated proof to a file.
-    //std::fs::write("/workspace/proof.json", b"this is a proof a.")?;
-
-    //return three files for Verifier
-    task.result(vec![], vec![String::from("/workspace/proof.json"),String::from("/workspace/stark_verfier.circom"),String::from("/workspace/test.log")])
+    let outcome = Outcome::success();
+    report.finish(&outcome, &workspace_file("report.json"))?;
 
+    //return the proof, the circom verifier, the log and the report for Verifier
+    let mut files = outputs;
+    files.push(workspace_file("test.log"));
+    files.push(workspace_file("report.json"));
+    Ok(TaskOutput::new(outcome.to_bytes(), files))
 }
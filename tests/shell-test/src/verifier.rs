@@ -1,31 +1,144 @@
 extern crate clap;
 use clap::{command, Parser};
 
-
-use starky::prove::stark_prove;
-
+use starky::{
+    digest::ElementDigest,
+    merklehash::MerkleTreeGL,
+    merklehash_bn128::MerkleTreeBN128,
+    polsarray::{PolKind, PolsArray},
+    stark_gen::StarkProof,
+    stark_setup::StarkSetup,
+    stark_verify::stark_verify,
+    traits::{MerkleTree, Transcript},
+    transcript::TranscriptGL,
+    transcript_bn128::TranscriptBN128,
+    types::{load_json, StarkStruct, PIL},
+};
 use std::time::Instant;
-use std::fs;
-
-
 
-use gevulot_common::WORKSPACE_PATH;
+use eigen_gevulot_common::args::parse_task_args;
+use eigen_gevulot_common::outcome::Verdict;
+use eigen_gevulot_common::report::task_verdict;
+use eigen_gevulot_common::stark::StarkParams;
+use eigen_gevulot_common::task::{self, workspace_file, TaskOutput};
 use gevulot_shim::{Task, TaskResult};
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-fn main()-> Result<()>  {
-   gevulot_shim::run(run_task)
+#[derive(Debug, Parser, Default)]
+#[command(about, version, no_binary_name(true))]
+struct Cli {
+    #[arg(short, long = "stark_stuct", default_value = "/gevulot/starkStruct.json")]
+    stark_struct: String,
+    #[arg(short, long = "piljson", default_value = "/workspace/pil.json")]
+    piljson: String,
+    #[arg(long = "const_pols", default_value = "/workspace/pols.const")]
+    const_pols: String,
+    /// The proof written by the prover's `--proof_file`.
+    #[arg(long = "in_file", default_value = "/workspace/proof.json")]
+    proof_file: String,
+    /// Reject proofs whose StarkStruct gives fewer bits of conjectured security.
+    #[arg(long = "min_security_bits")]
+    min_security_bits: Option<u32>,
+}
+
+fn main() -> Result<()> {
+    if task::is_local() {
+        return task::run_local(verify_task);
+    }
+    gevulot_shim::run(run_task)
 }
 
 fn run_task(task: Task) -> Result<TaskResult> {
+    let output = verify_task(&task.args)?;
+    task.result(output.data, output.files)
+}
+
+fn verify_task(task_args: &[String]) -> Result<TaskOutput> {
+    env_logger::init();
+    println!("verifier : task.args: {:?}", task_args);
+
+    //return the files generated by the prover to the gevulot's client.
+    let outputs: Vec<String> = ["proof.json", "stark_verfier.circom"]
+        .into_iter()
+        .map(workspace_file)
+        .collect();
+
+    let args = match parse_task_args::<Cli>(task_args) {
+        Ok(args) => args,
+        Err(e) => {
+            let verdict = Verdict {
+                verified: false,
+                reason: format!("the verifier has invalid arguments: {e}"),
+                publics: Vec::new(),
+            };
+            log::info!("{:?}", verdict);
+            return Ok(task_verdict(&verdict, &outputs));
+        }
+    };
+    log::info!(
+        "parameters: proof file:{}; stark_struct:{}; piljson:{}; const_pols:{}",
+        args.proof_file,
+        args.stark_struct,
+        args.piljson,
+        args.const_pols
+    );
+
+    let start = Instant::now();
+    let verdict = match verify(&args) {
+        Ok(true) => Verdict {
+            verified: true,
+            reason: "the STARK proof is valid".to_string(),
+            publics: Vec::new(),
+        },
+        Ok(false) => Verdict {
+            verified: false,
+            reason: "the STARK proof is invalid".to_string(),
+            publics: Vec::new(),
+        },
+        Err(e) => Verdict {
+            verified: false,
+            reason: format!("the STARK proof could not be verified: {e}"),
+            publics: Vec::new(),
+        },
+    };
+    log::info!("verification took {:?}: {:?}", start.elapsed(), verdict);
+
+    Ok(task_verdict(&verdict, &outputs))
+}
+
+fn verify(args: &Cli) -> Result<bool> {
+    if let Some(min_bits) = args.min_security_bits {
+        let level = StarkParams::load(args.stark_struct.as_ref())?.check_security(min_bits)?;
+        log::info!("security: {:?}", level);
+    }
+    let stark_struct = load_json::<StarkStruct>(&args.stark_struct)?;
+    match stark_struct.verificationHashType.as_str() {
+        "GL" => verify_with::<MerkleTreeGL, TranscriptGL>(args, &stark_struct),
+        "BN128" => verify_with::<MerkleTreeBN128, TranscriptBN128>(args, &stark_struct),
+        other => Err(format!("unsupported verificationHashType: {other}").into()),
+    }
+}
 
-    //env_logger::init();
-    println!("verifier : task.args: {:?}", &task.args);
-    
-    //In this test, the verifier does nothing. 
+/// Rebuilds the setup (const root, stark info and verifier program) from the
+/// PIL and constant polynomials, then checks the prover's proof against it.
+fn verify_with<M, T>(args: &Cli, stark_struct: &StarkStruct) -> Result<bool>
+where
+    M: MerkleTree<MTNode = ElementDigest<4>> + Send + Sync,
+    T: Transcript,
+{
+    let mut pil = load_json::<PIL>(&args.piljson)?;
+    let mut const_pol = PolsArray::new(&pil, PolKind::Constant);
+    const_pol.load(&args.const_pols)?;
 
+    let setup = StarkSetup::<M>::new(&const_pol, &mut pil, stark_struct, None)?;
+    let proof = load_json::<StarkProof<M>>(&args.proof_file)?;
 
-      //return  the files generated by the prover  to the gevulot's client.
-      task.result(vec![1,2,3,4,5,6,7,8,9], vec![String::from("/workspace/proof.json"),String::from("/workspace/stark_verfier.circom"),String::from("/workspace/test.log")])
+    Ok(stark_verify::<M, T>(
+        &proof,
+        &setup.const_root,
+        &setup.starkinfo,
+        stark_struct,
+        &setup.program,
+    )?)
 }